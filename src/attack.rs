@@ -0,0 +1,362 @@
+use crate::error::{Error, Result};
+use crate::source::{
+    Affix, CandidateSource, Chain, Dates, Hybrid, KeyboardLayout, KeyboardWalk, Markov,
+    MarkovStats, Mask, Prince, Rule, Rules, Slice, Stdin, Wordlist,
+};
+use crate::utils::audit::COMMON_SECRETS;
+use crate::utils::combination_generator::{
+    ByteLengthGenerator, CombinationGenerator, DIGITS, LOWERCASE, SPECIAL, UPPERCASE,
+    charset_warnings, normalize_charset, parse_byte_charset,
+};
+use crate::utils::jwt::KeyEncoding;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 爆破使用的字符集
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Charset {
+    /// 数字和大小写字母
+    Default,
+    /// 按类别组合的预设字符集
+    Options {
+        lowercase: bool,
+        uppercase: bool,
+        digits: bool,
+        special: bool,
+    },
+    /// 用户自定义的字符集
+    Custom(String),
+    /// 按字节定义的字符集，例如 `\x00-\xff`，见 [`parse_byte_charset`]
+    Bytes(String),
+}
+
+impl Charset {
+    /// 字符集是否为空
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Charset::Bytes(spec) => parse_byte_charset(spec).is_ok_and(|bytes| bytes.is_empty()),
+            _ => self.chars().is_empty(),
+        }
+    }
+
+    /// 每个符号编码后的字节，普通字符按 UTF-8 编码，字节字符集中每个符号是单个字节
+    pub fn symbols(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(match self {
+            Charset::Bytes(spec) => parse_byte_charset(spec)?
+                .into_iter()
+                .map(|byte| vec![byte])
+                .collect(),
+            _ => self
+                .chars()
+                .chars()
+                .map(|c| c.to_string().into_bytes())
+                .collect(),
+        })
+    }
+
+    /// 字符集中可能导致意外结果的地方，见 [`charset_warnings`]
+    pub fn warnings(&self) -> Vec<String> {
+        match self {
+            Charset::Custom(charset) => charset_warnings(charset),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 展开后的全部字符，自定义字符集会规范化并去重，字节字符集原样返回定义
+    pub fn chars(&self) -> String {
+        match self {
+            Charset::Default => format!("{}{}{}", DIGITS, LOWERCASE, UPPERCASE),
+            Charset::Options {
+                lowercase,
+                uppercase,
+                digits,
+                special,
+            } => {
+                let mut charset = String::new();
+                for (enabled, chars) in [
+                    (lowercase, LOWERCASE),
+                    (uppercase, UPPERCASE),
+                    (digits, DIGITS),
+                    (special, SPECIAL),
+                ] {
+                    if *enabled {
+                        charset.push_str(chars);
+                    }
+                }
+                charset
+            }
+            Charset::Custom(charset) => normalize_charset(charset),
+            Charset::Bytes(spec) => spec.clone(),
+        }
+    }
+}
+
+/// 暴力破解中最小、最大长度的单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthUnit {
+    /// 按字符计算，多字节字符的密钥会比长度更长
+    #[default]
+    Chars,
+    /// 按 UTF-8 编码后的字节计算
+    Bytes,
+}
+
+/// 一个攻击阶段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode")]
+pub enum AttackMode {
+    /// 内置的常见弱密钥
+    Common,
+    /// 按字符集穷举
+    BruteForce {
+        charset: Charset,
+        min_length: usize,
+        max_length: usize,
+        #[serde(default)]
+        length_unit: LengthUnit,
+    },
+    /// 按 `JwtCrackerCLI train` 生成的统计文件排序的暴力破解
+    Markov {
+        charset: Charset,
+        min_length: usize,
+        max_length: usize,
+        stats: PathBuf,
+        /// 每一位只取最可能的这么多个字符，0 为不裁剪
+        #[serde(default)]
+        threshold: usize,
+    },
+    /// hashcat 风格的掩码
+    Mask {
+        mask: String,
+        #[serde(default)]
+        custom_charsets: Vec<String>,
+    },
+    /// 字典
+    Wordlist { path: PathBuf },
+    /// 字典加变形规则，每行一条规则
+    Rules { path: PathBuf, rules: Vec<String> },
+    /// 字典与掩码拼接
+    Hybrid {
+        path: PathBuf,
+        mask: String,
+        #[serde(default)]
+        mask_first: bool,
+    },
+    /// 把字典中的词拼接成链，长度按字节计算
+    Prince {
+        path: PathBuf,
+        min_length: usize,
+        max_length: usize,
+        min_elements: usize,
+        max_elements: usize,
+    },
+    /// 沿键盘上相邻的键行走，例如 `qwerty`、`1qaz2wsx`
+    KeyboardWalk {
+        #[serde(default)]
+        layout: KeyboardLayout,
+        min_length: usize,
+        max_length: usize,
+        /// 最多改变几次方向
+        max_turns: usize,
+        /// 每个行走后面再拼接这个掩码的所有组合，例如 `?d?d?d` 可以得到 `qwerty123`
+        #[serde(default)]
+        mask: Option<String>,
+    },
+    /// 范围内的每一天按给定格式输出，例如 `20231231`、`31-12-2023`
+    Dates {
+        /// `YYYY-MM-DD`，包含当天
+        start: String,
+        end: String,
+        /// 为空时使用内置的常见格式
+        #[serde(default)]
+        formats: Vec<String>,
+    },
+    /// 从标准输入读取
+    Stdin,
+}
+
+impl AttackMode {
+    /// 创建这个阶段对应的候选来源
+    pub fn build_source(&self) -> Result<Box<dyn CandidateSource>> {
+        Ok(match self {
+            AttackMode::Common => Box::new(Wordlist::from_words(COMMON_SECRETS)),
+            AttackMode::BruteForce {
+                charset,
+                min_length,
+                max_length,
+                length_unit,
+            } => {
+                if charset.is_empty() {
+                    return Err(Error::InvalidConfig("字符集不能为空".to_string()));
+                }
+                match length_unit {
+                    LengthUnit::Chars => Box::new(CombinationGenerator::new_with_symbols(
+                        *min_length,
+                        *max_length,
+                        charset.symbols()?,
+                        None,
+                    )),
+                    LengthUnit::Bytes => Box::new(ByteLengthGenerator::new(
+                        *min_length,
+                        *max_length,
+                        charset.symbols()?,
+                    )),
+                }
+            }
+            AttackMode::Markov {
+                charset,
+                min_length,
+                max_length,
+                stats,
+                threshold,
+            } => {
+                if charset.is_empty() {
+                    return Err(Error::InvalidConfig("字符集不能为空".to_string()));
+                }
+                if let Charset::Bytes(_) = charset {
+                    return Err(Error::InvalidConfig(
+                        "Markov 模式不支持字节字符集".to_string(),
+                    ));
+                }
+                Box::new(Markov::new(
+                    &MarkovStats::load(stats)?,
+                    &charset.chars(),
+                    *min_length,
+                    *max_length,
+                    *threshold,
+                ))
+            }
+            AttackMode::Mask {
+                mask,
+                custom_charsets,
+            } => Box::new(Mask::parse(mask, custom_charsets)?),
+            AttackMode::Wordlist { path } => Box::new(Wordlist::open(path)?),
+            AttackMode::Rules { path, rules } => {
+                let rules = Rule::parse_lines(&rules.join("\n"))?;
+                Box::new(Rules::new(Wordlist::open(path)?, rules))
+            }
+            AttackMode::Hybrid {
+                path,
+                mask,
+                mask_first,
+            } => Box::new(Hybrid::new(
+                Wordlist::open(path)?,
+                Mask::parse(mask, &[])?,
+                *mask_first,
+            )),
+            AttackMode::Prince {
+                path,
+                min_length,
+                max_length,
+                min_elements,
+                max_elements,
+            } => Box::new(Prince::new(
+                Wordlist::open(path)?,
+                *min_length,
+                *max_length,
+                *min_elements,
+                *max_elements,
+            )),
+            AttackMode::KeyboardWalk {
+                layout,
+                min_length,
+                max_length,
+                max_turns,
+                mask,
+            } => {
+                let walk = KeyboardWalk::new(*layout, *min_length, *max_length, *max_turns)?;
+                match mask {
+                    Some(mask) => Box::new(Hybrid::new(walk, Mask::parse(mask, &[])?, false)),
+                    None => Box::new(walk),
+                }
+            }
+            AttackMode::Dates {
+                start,
+                end,
+                formats,
+            } => Box::new(Dates::new(start, end, formats)?),
+            AttackMode::Stdin => Box::new(Stdin::new()),
+        })
+    }
+}
+
+/// 一次爆破的配置：按顺序执行的攻击阶段，以及统一的前后缀
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackConfig {
+    pub stages: Vec<AttackMode>,
+    /// 密钥前缀，不参与爆破
+    #[serde(default)]
+    pub prefix: String,
+    /// 密钥后缀，不参与爆破
+    #[serde(default)]
+    pub suffix: String,
+    /// 加上前后缀后的候选依次按这些方式解读，为空时只按原样校验
+    #[serde(default)]
+    pub key_encodings: Vec<KeyEncoding>,
+}
+
+impl Default for AttackConfig {
+    fn default() -> Self {
+        Self {
+            stages: vec![AttackMode::BruteForce {
+                charset: Charset::Default,
+                min_length: 1,
+                max_length: 10,
+                length_unit: LengthUnit::Chars,
+            }],
+            prefix: String::new(),
+            suffix: String::new(),
+            key_encodings: Vec::new(),
+        }
+    }
+}
+
+impl AttackConfig {
+    /// 各阶段字符集的警告，不影响运行
+    pub fn warnings(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .flat_map(|stage| match stage {
+                AttackMode::BruteForce { charset, .. } | AttackMode::Markov { charset, .. } => {
+                    charset.warnings()
+                }
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// 把所有阶段串联成一个候选来源，并加上前后缀
+    pub fn build_source(&self) -> Result<Box<dyn CandidateSource>> {
+        if self.stages.is_empty() {
+            return Err(Error::InvalidConfig("至少需要一个攻击阶段".to_string()));
+        }
+        let mut sources = self
+            .stages
+            .iter()
+            .map(AttackMode::build_source)
+            .collect::<Result<Vec<_>>>()?;
+        let source = if sources.len() == 1 {
+            sources.pop().unwrap()
+        } else {
+            Box::new(Chain::new(sources))
+        };
+        if self.prefix.is_empty() && self.suffix.is_empty() {
+            return Ok(source);
+        }
+        Ok(Box::new(Affix::new(
+            source,
+            self.prefix.as_bytes(),
+            self.suffix.as_bytes(),
+        )))
+    }
+    /// 只包含第 `start` 个起的 `len` 个候选的来源，用于把工作拆分给多台机器
+    pub fn build_slice(&self, start: u128, len: u128) -> Result<Box<dyn CandidateSource>> {
+        let source = self.build_source()?;
+        let description = source.describe();
+        Slice::new(source, start, len)
+            .map(|slice| Box::new(slice) as Box<dyn CandidateSource>)
+            .ok_or_else(|| Error::InvalidConfig(format!("{} 不支持拆分", description)))
+    }
+}
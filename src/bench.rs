@@ -0,0 +1,110 @@
+//! 测量本机的爆破速度，并估算穷举各个长度需要的时间
+
+use crate::cancel::CancelToken;
+use crate::cracker::{Cracker, RunOptions};
+use crate::error::Result;
+use crate::event::{Event, Stats};
+use crate::utils::combination_generator::CombinationGenerator;
+use crate::utils::jwt::{HmacAlgorithm, sign_hmac};
+use crossbeam_channel::bounded;
+use serde_json::{Value, json};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// 一组参数下的测量结果
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub algorithm: HmacAlgorithm,
+    pub key_length: usize,
+    pub threads: usize,
+    pub tried: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    /// 每秒尝试的候选数量
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.tried as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "alg": self.algorithm.name(),
+            "key_length": self.key_length,
+            "threads": self.threads,
+            "tried": self.tried,
+            "duration_secs": self.elapsed.as_secs_f64(),
+            "rate": self.rate(),
+        })
+    }
+}
+
+/// 用完整的爆破引擎跑 `duration` 这么久，候选是长度为 `key_length` 的默认字符集组合
+pub fn bench(
+    algorithm: HmacAlgorithm,
+    key_length: usize,
+    threads: usize,
+    duration: Duration,
+) -> Result<BenchResult> {
+    // 密钥里带一个不在字符集中的字节，保证永远找不到
+    let token = sign_hmac(&json!({}), &json!({}), algorithm, b"\0bench");
+    let cracker = Cracker::new(&token)?.options(RunOptions {
+        threads: Some(threads),
+        ..RunOptions::default()
+    });
+    let source = CombinationGenerator::new(key_length, key_length, None);
+
+    let cancel = CancelToken::new();
+    let last = Mutex::new(None::<Stats>);
+    let used_threads = AtomicUsize::new(threads);
+    let (done_tx, done_rx) = bounded::<()>(0);
+    let result = thread::scope(|scope| {
+        let timer = cancel.clone();
+        // 到时间或者候选提前耗尽（done_tx 被丢弃）时结束
+        scope.spawn(move || {
+            let _ = done_rx.recv_timeout(duration);
+            timer.cancel();
+        });
+        let result = cracker.run(source, &cancel, |event| {
+            if let Event::Started { threads, .. } = event {
+                used_threads.store(*threads, Ordering::Relaxed);
+            }
+            if let Some(stats) = event.stats() {
+                *last.lock().unwrap() = Some(stats.clone());
+            }
+        });
+        drop(done_tx);
+        result
+    });
+    result?;
+
+    let stats = last.into_inner().unwrap().unwrap_or(Stats {
+        tried: 0,
+        elapsed: Duration::ZERO,
+        sample: Vec::new(),
+        keyspace: None,
+        offset: 0,
+    });
+    Ok(BenchResult {
+        algorithm,
+        key_length,
+        threads: used_threads.into_inner(),
+        tried: stats.tried,
+        elapsed: stats.elapsed,
+    })
+}
+
+/// 按 `rate` 穷举 `charset_len` 个字符、长度为 `length` 的全部组合需要的秒数
+pub fn projected_secs(rate: f64, charset_len: usize, length: usize) -> f64 {
+    if rate <= 0.0 {
+        return f64::INFINITY;
+    }
+    (charset_len as f64).powi(length as i32) / rate
+}
@@ -0,0 +1,14 @@
+//! audit 子命令
+
+use crate::{EXIT_FOUND, OutputFormat};
+use jwt_cracker::Result;
+use jwt_cracker::utils::audit::audit_token;
+
+pub(crate) fn run_audit(token: &str, secret: Option<&str>, output: OutputFormat) -> Result<i32> {
+    let report = audit_token(token, secret)?;
+    match output {
+        OutputFormat::Text => print!("{}", report.to_text()),
+        _ => output.print(&report.to_json()),
+    }
+    Ok(EXIT_FOUND)
+}
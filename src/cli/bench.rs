@@ -0,0 +1,131 @@
+//! bench 子命令
+
+use crate::{EXIT_FOUND, OutputFormat};
+use indicatif::HumanCount;
+use jwt_cracker::Result;
+use jwt_cracker::bench::{BenchResult, bench, projected_secs};
+use jwt_cracker::utils::jwt::HmacAlgorithm;
+use jwt_cracker::utils::time::format_duration;
+use serde_json::{Value, json};
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// bench 子命令整理后的参数
+pub(crate) struct BenchArgs {
+    pub(crate) algs: Vec<HmacAlgorithm>,
+    pub(crate) lengths: Vec<usize>,
+    pub(crate) threads: Vec<usize>,
+    pub(crate) duration: Duration,
+    pub(crate) lengths_to_project: RangeInclusive<usize>,
+    pub(crate) charset_len: usize,
+}
+
+pub(crate) fn run_bench(args: &BenchArgs, output: OutputFormat) -> Result<i32> {
+    let algs = if args.algs.is_empty() {
+        vec![
+            HmacAlgorithm::HS256,
+            HmacAlgorithm::HS384,
+            HmacAlgorithm::HS512,
+        ]
+    } else {
+        args.algs.clone()
+    };
+    let threads = if args.threads.is_empty() {
+        let mut threads = vec![1, rayon::current_num_threads()];
+        threads.dedup();
+        threads
+    } else {
+        args.threads.clone()
+    };
+
+    if output == OutputFormat::Text {
+        println!(
+            "{:<6} {:>8} {:>6} {:>15}",
+            "算法", "密钥长度", "线程", "速度(个/秒)"
+        );
+    }
+    let mut results = Vec::new();
+    for &alg in &algs {
+        for &length in &args.lengths {
+            for &thread_count in &threads {
+                let result = bench(alg, length, thread_count, args.duration)?;
+                match output {
+                    OutputFormat::Text => println!(
+                        "{:<8} {:>12} {:>8} {:>19}",
+                        alg.name(),
+                        length,
+                        result.threads,
+                        HumanCount(result.rate() as u64).to_string()
+                    ),
+                    OutputFormat::Jsonl => output.print(&result.to_json()),
+                    OutputFormat::Json => {}
+                }
+                results.push(result);
+            }
+        }
+    }
+
+    // 每个算法取最快的一组结果来估算穷举时间
+    let mut projections = Vec::new();
+    for &alg in &algs {
+        let Some(best) = results
+            .iter()
+            .filter(|r| r.algorithm == alg)
+            .max_by(|a, b| a.rate().total_cmp(&b.rate()))
+        else {
+            continue;
+        };
+        let rate = best.rate();
+        if output == OutputFormat::Text {
+            println!(
+                "\n{} 在 {} 线程下穷举 {} 个字符的预计时间:",
+                alg.name(),
+                best.threads,
+                args.charset_len
+            );
+        }
+        let mut lengths = Vec::new();
+        for length in args.lengths_to_project.clone() {
+            let secs = projected_secs(rate, args.charset_len, length);
+            let keyspace = (args.charset_len as u128).checked_pow(length as u32);
+            if output == OutputFormat::Text {
+                let keyspace = match keyspace.and_then(|k| u64::try_from(k).ok()) {
+                    Some(k) => HumanCount(k).to_string(),
+                    None => format!("{:.3e}", (args.charset_len as f64).powi(length as i32)),
+                };
+                println!(
+                    "  {:>2} 位 {:>26} 个  {}",
+                    length,
+                    keyspace,
+                    format_duration(secs)
+                );
+            }
+            lengths.push(json!({
+                "length": length,
+                "keyspace": keyspace.map(|k| k.to_string()),
+                "secs": secs.is_finite().then_some(secs),
+            }));
+        }
+        projections.push(json!({
+            "alg": alg.name(),
+            "threads": best.threads,
+            "rate": rate,
+            "charset_len": args.charset_len,
+            "lengths": lengths,
+        }));
+    }
+
+    if output != OutputFormat::Text {
+        let results: Vec<Value> = results.iter().map(BenchResult::to_json).collect();
+        let summary = json!({ "results": results, "projections": projections });
+        match output {
+            OutputFormat::Jsonl => {
+                for projection in &projections {
+                    output.print(projection);
+                }
+            }
+            _ => output.print(&summary),
+        }
+    }
+    Ok(EXIT_FOUND)
+}
@@ -0,0 +1,70 @@
+//! 分布式爆破的 coordinate 和 worker 子命令
+
+use crate::crack::{AttackArgs, ProgressView, report};
+use crate::{EXIT_FOUND, OutputFormat, install_ctrlc};
+use jwt_cracker::cluster::{self, ClusterEvent, Coordinator, WorkerEvent};
+use jwt_cracker::{Result, RunOptions, Verifier};
+
+/// 分发工作单元直到某个工作端找到密钥或所有单元完成
+pub(crate) fn run_coordinate(
+    token: &str,
+    attack: &AttackArgs,
+    listen: &str,
+    unit_size: u64,
+    output: OutputFormat,
+) -> Result<i32> {
+    let coordinator = Coordinator::bind(listen, token, attack.config()?, u128::from(unit_size))?;
+    let alg = Verifier::new(token)?.algorithm().name();
+    let cancel = install_ctrlc();
+    eprintln!("正在监听 {}，等待工作端连接", coordinator.local_addr()?);
+
+    let view = ProgressView::new(output);
+    let found = coordinator.run(&cancel, |event| match event {
+        ClusterEvent::WorkerJoined { peer, threads } => {
+            view.bar
+                .suspend(|| eprintln!("工作端 {} 已连接，{} 个线程", peer, threads));
+        }
+        ClusterEvent::WorkerLeft { peer, requeued } => {
+            let requeued = requeued
+                .map(|id| format!("，单元 {} 重新排队", id))
+                .unwrap_or_default();
+            view.bar
+                .suspend(|| eprintln!("工作端 {} 已断开{}", peer, requeued));
+        }
+        ClusterEvent::UnitMismatch {
+            peer,
+            unit,
+            tried,
+            expected,
+        } => {
+            view.bar.suspend(|| {
+                eprintln!(
+                    "警告: 工作端 {} 报告单元 {} 完成，但只尝试了 {} 个（应为 {} 个）",
+                    peer, unit, tried, expected
+                )
+            });
+        }
+        ClusterEvent::Engine(event) => view.show(event),
+    })?;
+    let (code, last) = view.finish(found.is_some());
+    report(token, alg, code, last, found, output);
+    Ok(code)
+}
+
+/// 作为工作端运行，直到协调端要求停止或断开
+pub(crate) fn run_worker(connect: &str, options: RunOptions) -> Result<i32> {
+    let cancel = install_ctrlc();
+    eprintln!("正在连接 {}", connect);
+    cluster::run_worker(connect, options, &cancel, |event| match event {
+        WorkerEvent::Connected { description } => eprintln!("已连接: {}", description),
+        WorkerEvent::UnitStarted { id, start, len } => {
+            eprintln!("单元 {}: 从第 {} 个起的 {} 个候选", id, start, len)
+        }
+        WorkerEvent::UnitFinished { id, tried } => {
+            eprintln!("单元 {} 完成，尝试了 {} 个", id, tried)
+        }
+        WorkerEvent::Found(found) => eprintln!("找到密钥: {}", found.secret_lossy()),
+    })?;
+    eprintln!("已停止");
+    Ok(EXIT_FOUND)
+}
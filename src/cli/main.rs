@@ -0,0 +1,418 @@
+mod audit;
+mod bench;
+mod cluster;
+mod crack;
+mod forge;
+mod job;
+mod oracle;
+mod serve;
+mod token;
+mod train;
+
+use crate::audit::run_audit;
+use crate::bench::{BenchArgs, run_bench};
+use crate::cluster::{run_coordinate, run_worker};
+use crate::crack::{AttackArgs, run_crack};
+use crate::forge::{ForgeArgs, run_forge, run_forge_psychic};
+use crate::job::run_job;
+use crate::oracle::{OracleArgs, run_oracle};
+use crate::serve::run_serve;
+use crate::token::{run_decode, run_encode};
+use crate::train::run_train;
+use clap::{Parser, Subcommand, ValueEnum};
+use jwt_cracker::server::ServeOptions;
+use jwt_cracker::utils::forge::{KeyDelivery, SignatureEncoding};
+use jwt_cracker::utils::jwt::{HmacAlgorithm, KeyEncoding};
+use jwt_cracker::utils::time::parse_duration;
+use jwt_cracker::{CancelToken, Charset, Error, Result, RunOptions};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// 要破解的JWT，等同于 crack 子命令
+    #[arg(short, long, required_unless_present = "resume")]
+    token: Option<String>,
+
+    #[command(flatten)]
+    attack: AttackArgs,
+
+    /// 输出格式，进度始终只写到 stderr
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// 等同于 --output jsonl
+    #[arg(long, global = true)]
+    jsonl: bool,
+}
+
+impl Args {
+    fn output(&self) -> OutputFormat {
+        if self.jsonl {
+            OutputFormat::Jsonl
+        } else {
+            self.output
+        }
+    }
+}
+
+/// 结果的输出格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// 人类可读的文本
+    Text,
+    /// 结束时输出一个 JSON 文档
+    Json,
+    /// 每行一个 JSON，crack 会逐行输出进度事件
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// 按 JSON 或 JSONL 格式把结果写到 stdout
+    fn print(self, value: &Value) {
+        match self {
+            OutputFormat::Json => println!("{:#}", value),
+            _ => println!("{}", value),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 爆破HMAC密钥，不写子命令时的默认行为
+    Crack {
+        /// 要破解的JWT
+        #[arg(short, long, required_unless_present = "resume")]
+        token: Option<String>,
+
+        #[command(flatten)]
+        attack: AttackArgs,
+    },
+    /// 启动本地 HTTP 服务，通过 REST 接口提交和查询破解任务
+    Serve {
+        /// 监听地址，端口为 0 时由系统分配
+        #[arg(long, default_value = "127.0.0.1:8087")]
+        addr: String,
+
+        /// 排队任务的上限
+        #[arg(long, default_value_t = 16)]
+        queue_size: usize,
+
+        /// 同时运行的任务数
+        #[arg(long, default_value_t = 1)]
+        workers: usize,
+    },
+    /// 分布式爆破的协调端，把候选空间切成单元分给连接上来的工作端
+    Coordinate {
+        /// 要破解的JWT
+        #[arg(short, long)]
+        token: String,
+
+        /// 只使用其中的攻击参数，线程等设置由各个工作端自己指定
+        #[command(flatten)]
+        attack: AttackArgs,
+
+        /// 监听地址，协议没有认证，只应在可信的网络中使用
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: String,
+
+        /// 每个工作单元的候选数量
+        #[arg(long, default_value_t = 10_000_000)]
+        unit_size: u64,
+    },
+    /// 分布式爆破的工作端，连接协调端并处理它分配的单元
+    Worker {
+        /// 协调端地址，例如 192.168.1.10:7878
+        #[arg(long)]
+        connect: String,
+
+        /// 线程数，默认使用全部核心
+        #[arg(long)]
+        threads: Option<usize>,
+
+        /// 以最低优先级运行
+        #[arg(long)]
+        low_priority: bool,
+
+        /// 把每个工作线程绑定到一个CPU核心
+        #[arg(long)]
+        pin_cores: bool,
+    },
+    /// 执行 TOML 或 JSON 格式的任务文件
+    Run {
+        /// 任务文件，扩展名为 .json 时按 JSON 解析，否则按 TOML 解析
+        job: PathBuf,
+    },
+    /// 从字典统计字符频率，生成 Markov 模式使用的统计文件
+    Train {
+        /// 训练用的字典，例如泄露的密码或密钥
+        #[arg(short, long)]
+        wordlist: PathBuf,
+
+        /// 统计文件的输出路径
+        #[arg(short, long, default_value = "markov.json")]
+        out: PathBuf,
+    },
+    /// 解码并格式化输出Header和Payload
+    Decode {
+        #[arg(short, long)]
+        token: String,
+    },
+    /// 用HMAC密钥签发令牌
+    Encode {
+        /// Payload，JSON格式
+        #[arg(short, long)]
+        payload: String,
+
+        /// Header，JSON格式，其中的 alg 会被 --alg 覆盖
+        #[arg(long, default_value = r#"{"typ":"JWT"}"#)]
+        header: String,
+
+        #[arg(short, long)]
+        secret: String,
+
+        #[arg(long, default_value = "HS256", value_parser = parse_hmac_algorithm)]
+        alg: HmacAlgorithm,
+
+        /// 密钥的编码，签名时使用解码后的字节
+        #[arg(long, default_value = "raw", value_parser = parse_key_encoding)]
+        key_encoding: KeyEncoding,
+    },
+    /// 用本地生成的密钥伪造 jwk / jku / x5u 令牌，或生成 psychic signature
+    Forge {
+        /// 从这个令牌中取Header和Payload
+        #[arg(short, long, required_unless_present = "payload")]
+        token: Option<String>,
+
+        /// 代替令牌中的Payload，JSON格式
+        #[arg(short, long)]
+        payload: Option<String>,
+
+        /// 签名算法，RS*/PS*/ES256/ES384，默认 RS256；--psychic 时为 ES256/ES384/ES512，默认 ES256
+        #[arg(long)]
+        alg: Option<String>,
+
+        /// 通过 jku 指向这个地址，默认把公钥内嵌到 jwk
+        #[arg(long, conflicts_with = "x5u")]
+        jku: Option<String>,
+
+        /// 通过 x5u 指向这个地址
+        #[arg(long)]
+        x5u: Option<String>,
+
+        /// 把需要部署的文档写到这个文件：jku 为 JWKS，x5u 为 PEM 证书
+        #[arg(long, alias = "jwks-out")]
+        key_out: Option<PathBuf>,
+
+        /// 生成 r=0、s=0 的 ECDSA 签名 (CVE-2022-21449)
+        #[arg(long, conflicts_with_all = ["jku", "x5u", "key_out"])]
+        psychic: bool,
+
+        /// psychic signature 使用 DER 编码
+        #[arg(long, requires = "psychic")]
+        der: bool,
+    },
+    /// 测量本机的爆破速度，并估算穷举各个长度需要的时间
+    Bench {
+        /// 要测量的算法，可重复指定，默认全部
+        #[arg(long = "alg", value_parser = parse_hmac_algorithm)]
+        algs: Vec<HmacAlgorithm>,
+
+        /// 候选密钥的长度，可重复指定
+        #[arg(short = 'l', long = "length", default_values_t = [8])]
+        lengths: Vec<usize>,
+
+        /// 线程数，可重复指定，默认为 1 和全部核心
+        #[arg(long = "threads")]
+        threads: Vec<usize>,
+
+        /// 每组参数测量的秒数
+        #[arg(long, default_value_t = 1.0)]
+        seconds: f64,
+
+        /// 估算耗时的最小长度
+        #[arg(short = 'm', long = "min", default_value_t = 1)]
+        min_length: usize,
+
+        /// 估算耗时的最大长度
+        #[arg(short = 'x', long = "max", default_value_t = 10)]
+        max_length: usize,
+
+        /// 估算耗时使用的字符集，默认为数字和大小写字母
+        #[arg(short = 'c', long)]
+        charset: Option<String>,
+    },
+    /// 把原始令牌和各种伪造令牌发给目标接口，根据响应判断哪些被接受
+    Oracle {
+        #[command(flatten)]
+        args: OracleArgs,
+    },
+    /// 静态审计JWT的安全问题
+    Audit {
+        #[arg(short, long)]
+        token: String,
+
+        /// 已知的密钥，会一并标记为弱密钥
+        #[arg(short, long)]
+        secret: Option<String>,
+    },
+}
+
+fn parse_hmac_algorithm(name: &str) -> std::result::Result<HmacAlgorithm, String> {
+    HmacAlgorithm::from_name(&name.to_ascii_uppercase())
+        .ok_or_else(|| "只支持 HS256、HS384、HS512".to_string())
+}
+
+fn parse_key_encoding(name: &str) -> std::result::Result<KeyEncoding, String> {
+    KeyEncoding::from_name(&name.to_ascii_lowercase())
+        .ok_or_else(|| "只支持 raw、base64、base64url、hex".to_string())
+}
+
+fn parse_duration_arg(text: &str) -> std::result::Result<Duration, String> {
+    parse_duration(text).ok_or_else(|| "时长格式应为 90、30s、10m、1h30m 或 2d".to_string())
+}
+
+/// 解析命令行中传入的JSON，`name` 用于错误信息
+fn parse_json(name: &'static str, text: &str) -> Result<Value> {
+    serde_json::from_str(text).map_err(|source| Error::Json {
+        segment: name,
+        source,
+    })
+}
+
+/// 找到密钥，其它子命令执行成功
+const EXIT_FOUND: i32 = 0;
+/// 所有候选都试完了仍未找到
+const EXIT_EXHAUSTED: i32 = 1;
+/// 被 Ctrl-C 中断
+const EXIT_CANCELLED: i32 = 3;
+/// 令牌、参数或文件等错误；clap 的用法错误固定为 2
+const EXIT_ERROR: i32 = 4;
+/// 达到 --max-time 或 --max-candidates
+const EXIT_LIMIT: i32 = 5;
+
+// 核心的命令行逻辑
+pub fn main() {
+    let args = Args::parse();
+    let output = args.output();
+    let result = match args.command {
+        Some(Command::Crack { token, attack }) => run_crack(token.as_deref(), &attack, output),
+        Some(Command::Run { job }) => run_job(&job, output),
+        Some(Command::Train { wordlist, out }) => run_train(&wordlist, &out, output),
+        Some(Command::Coordinate {
+            token,
+            attack,
+            listen,
+            unit_size,
+        }) => run_coordinate(&token, &attack, &listen, unit_size, output),
+        Some(Command::Worker {
+            connect,
+            threads,
+            low_priority,
+            pin_cores,
+        }) => run_worker(
+            &connect,
+            RunOptions {
+                threads,
+                low_priority,
+                pin_cores,
+                ..RunOptions::default()
+            },
+        ),
+        Some(Command::Serve {
+            addr,
+            queue_size,
+            workers,
+        }) => run_serve(&ServeOptions {
+            addr,
+            queue_size,
+            workers,
+        }),
+        Some(Command::Decode { token }) => run_decode(&token, output),
+        Some(Command::Encode {
+            payload,
+            header,
+            secret,
+            alg,
+            key_encoding,
+        }) => run_encode(&header, &payload, &secret, alg, key_encoding, output),
+        Some(Command::Forge {
+            token,
+            payload,
+            alg,
+            jku,
+            x5u,
+            key_out,
+            psychic,
+            der,
+        }) => {
+            let delivery = match (jku, x5u) {
+                (Some(url), _) => KeyDelivery::Jku(url),
+                (_, Some(url)) => KeyDelivery::X5u(url),
+                _ => KeyDelivery::EmbeddedJwk,
+            };
+            let encoding = if der {
+                SignatureEncoding::Der
+            } else {
+                SignatureEncoding::Raw
+            };
+            let alg = alg.unwrap_or_else(|| if psychic { "ES256" } else { "RS256" }.to_string());
+            let forge = ForgeArgs {
+                token: token.as_deref(),
+                payload: payload.as_deref(),
+                alg: &alg,
+            };
+            if psychic {
+                run_forge_psychic(&forge, encoding, output)
+            } else {
+                run_forge(&forge, &delivery, key_out.as_deref(), output)
+            }
+        }
+        Some(Command::Bench {
+            algs,
+            lengths,
+            threads,
+            seconds,
+            min_length,
+            max_length,
+            charset,
+        }) => {
+            let charset = charset.map_or(Charset::Default, Charset::Custom);
+            let bench = BenchArgs {
+                algs,
+                lengths,
+                threads,
+                duration: Duration::from_secs_f64(seconds.max(0.01)),
+                lengths_to_project: min_length..=max_length,
+                charset_len: charset.chars().chars().count(),
+            };
+            run_bench(&bench, output)
+        }
+        Some(Command::Audit { token, secret }) => run_audit(&token, secret.as_deref(), output),
+        Some(Command::Oracle { args }) => run_oracle(&args, output),
+        None => run_crack(args.token.as_deref(), &args.attack, output),
+    };
+    let code = result.unwrap_or_else(|e| {
+        match output {
+            OutputFormat::Text => eprintln!("错误: {}", e),
+            _ => output.print(&json!({ "status": "error", "error": e.to_string() })),
+        }
+        EXIT_ERROR
+    });
+    std::process::exit(code);
+}
+
+/// Ctrl-C 时取消返回的令牌
+fn install_ctrlc() -> CancelToken {
+    let cancel = CancelToken::new();
+    let handler_token = cancel.clone();
+    // 重复注册只会在库内部报错，这里忽略即可
+    let _ = ctrlc::set_handler(move || handler_token.cancel());
+    cancel
+}
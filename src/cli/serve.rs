@@ -0,0 +1,19 @@
+//! serve 子命令
+
+use crate::EXIT_FOUND;
+use jwt_cracker::Result;
+use jwt_cracker::server::{ServeOptions, Server};
+
+/// 运行 HTTP 服务直到 Ctrl-C
+pub(crate) fn run_serve(options: &ServeOptions) -> Result<i32> {
+    let server = Server::bind(options)?;
+    let handle = server.shutdown_handle();
+    let _ = ctrlc::set_handler(move || handle.shutdown());
+    match server.local_addr() {
+        Some(addr) => eprintln!("正在监听 http://{}，按 Ctrl-C 停止", addr),
+        None => eprintln!("正在监听 {}，按 Ctrl-C 停止", options.addr),
+    }
+    server.run()?;
+    eprintln!("服务已停止");
+    Ok(EXIT_FOUND)
+}
@@ -0,0 +1,63 @@
+//! decode 和 encode 子命令
+
+use crate::{EXIT_FOUND, OutputFormat, parse_json};
+use jwt_cracker::utils::claims::describe_time_claims;
+use jwt_cracker::utils::jwt::{
+    HmacAlgorithm, KeyEncoding, decode_json_segment, sign_hmac, time_claim_warnings,
+};
+use jwt_cracker::{Error, Result, split_token};
+use serde_json::{Value, json};
+
+pub(crate) fn run_decode(token: &str, output: OutputFormat) -> Result<i32> {
+    let parts = split_token(token)?;
+    let header = decode_json_segment("header", parts.header)?;
+    let payload = decode_json_segment("payload", parts.payload)?;
+    let time_claims = describe_time_claims(&payload);
+    let warnings: Vec<String> = time_claim_warnings(&payload)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    if output == OutputFormat::Text {
+        println!("Header:\n{:#}", header);
+        println!("Payload:\n{:#}", payload);
+        for (name, time) in &time_claims {
+            println!("{}: {}", name, time);
+        }
+        for warning in &warnings {
+            println!("提示: {}", warning);
+        }
+    } else {
+        let time_claims: serde_json::Map<String, Value> = time_claims
+            .into_iter()
+            .map(|(name, time)| (name.to_string(), json!(time)))
+            .collect();
+        output.print(&json!({
+            "header": header,
+            "payload": payload,
+            "time_claims": time_claims,
+            "warnings": warnings,
+        }));
+    }
+    Ok(EXIT_FOUND)
+}
+
+pub(crate) fn run_encode(
+    header: &str,
+    payload: &str,
+    secret: &str,
+    alg: HmacAlgorithm,
+    key_encoding: KeyEncoding,
+    output: OutputFormat,
+) -> Result<i32> {
+    let header = parse_json("header", header)?;
+    let payload = parse_json("payload", payload)?;
+    let key = key_encoding
+        .decode(secret.as_bytes())
+        .ok_or_else(|| Error::InvalidConfig(format!("密钥不是合法的 {}", key_encoding.name())))?;
+    let token = sign_hmac(&header, &payload, alg, &key);
+    match output {
+        OutputFormat::Text => println!("{}", token),
+        _ => output.print(&json!({ "token": token, "alg": alg.name() })),
+    }
+    Ok(EXIT_FOUND)
+}
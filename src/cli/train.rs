@@ -0,0 +1,26 @@
+//! train 子命令
+
+use crate::{EXIT_FOUND, OutputFormat};
+use jwt_cracker::Result;
+use jwt_cracker::source::{MarkovStats, Wordlist};
+use serde_json::json;
+use std::path::Path;
+
+pub(crate) fn run_train(wordlist: &Path, out: &Path, output: OutputFormat) -> Result<i32> {
+    let stats = MarkovStats::train(&mut Wordlist::open(wordlist)?);
+    stats.save(out)?;
+    match output {
+        OutputFormat::Text => println!(
+            "统计了 {} 个词、{} 位，已写入 {}",
+            stats.words(),
+            stats.positions.len(),
+            out.display()
+        ),
+        _ => output.print(&json!({
+            "words": stats.words(),
+            "positions": stats.positions.len(),
+            "out": out,
+        })),
+    }
+    Ok(EXIT_FOUND)
+}
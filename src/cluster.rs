@@ -0,0 +1,540 @@
+//! 分布式爆破：协调端把候选空间切成工作单元，通过 TCP 分发给多台机器上的工作端
+//!
+//! 双方每行发送一个 JSON 格式的 [`Message`]。工作端连接后先发 `hello`，协调端回复
+//! `job`，之后每次发一个 `unit`，工作端完成后回复 `done` 或 `found`，运行中定期发送
+//! `progress`。工作端断开时它手上的单元会重新排队，任何一端找到密钥后协调端向所有
+//! 工作端发送 `stop`。
+//!
+//! 协调端会重新校验 `found` 中的密钥，但无法验证 `done`：一个出错或不可信的工作端
+//! 可以谎称单元已经试完。报告的数量与单元大小不符时会发出 [`ClusterEvent::UnitMismatch`]。
+
+use crate::attack::AttackConfig;
+use crate::cancel::CancelToken;
+use crate::cracker::{Cracker, Found, RunOptions, STATS_INTERVAL};
+use crate::error::{Error, Result};
+use crate::event::{Event, Stats};
+use crate::utils::jwt::Verifier;
+use crossbeam_channel::unbounded;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 等待连接和读取消息时检查是否该停止的间隔
+const POLL: Duration = Duration::from_millis(100);
+
+/// 协调端和工作端之间的消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Message {
+    /// 工作端连接后发送，报告自己的线程数
+    Hello { threads: usize },
+    /// 要破解的令牌和攻击配置
+    Job { token: String, attack: AttackConfig },
+    /// 一个工作单元：从第 `start` 个候选起的 `len` 个
+    Unit { id: u64, start: u128, len: u128 },
+    /// 当前单元中已经尝试的数量
+    Progress {
+        unit: u64,
+        tried: u64,
+        sample: String,
+    },
+    /// 单元中的候选都试完了
+    Done { unit: u64, tried: u64 },
+    /// 找到了密钥
+    Found { unit: u64, secret: Vec<u8> },
+    /// 停止工作并断开
+    Stop,
+}
+
+/// 协调端上发生的事情
+#[derive(Debug, Clone)]
+pub enum ClusterEvent {
+    WorkerJoined {
+        peer: SocketAddr,
+        threads: usize,
+    },
+    /// 工作端断开，`requeued` 为重新排队的单元
+    WorkerLeft {
+        peer: SocketAddr,
+        requeued: Option<u64>,
+    },
+    /// 工作端报告单元完成，但尝试的数量与单元大小不符，这个单元可能没有试完
+    UnitMismatch {
+        peer: SocketAddr,
+        unit: u64,
+        tried: u64,
+        expected: u128,
+    },
+    /// 汇总后的进度，和单机爆破的事件相同
+    Engine(Event),
+}
+
+/// 工作端上发生的事情
+#[derive(Debug, Clone)]
+pub enum WorkerEvent {
+    Connected { description: String },
+    UnitStarted { id: u64, start: u128, len: u128 },
+    UnitFinished { id: u64, tried: u64 },
+    Found(Found),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Unit {
+    id: u64,
+    start: u128,
+    len: u128,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    next_start: u128,
+    /// 从断开的工作端收回的单元，优先分配
+    requeued: VecDeque<Unit>,
+    /// 正在进行的单元和它已尝试的数量
+    in_flight: HashMap<u64, (Unit, u64)>,
+    /// 已经完成的单元中的候选数
+    completed: u128,
+    sample: Vec<u8>,
+    found: Option<Found>,
+    stopped: bool,
+}
+
+impl State {
+    fn tried(&self) -> u128 {
+        self.completed
+            + self
+                .in_flight
+                .values()
+                .map(|(_, tried)| u128::from(*tried))
+                .sum::<u128>()
+    }
+}
+
+/// 所有连接共享的状态，状态变化时通知等待分配单元的连接和汇总线程
+struct Shared<'a> {
+    coordinator: &'a Coordinator,
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared<'_> {
+    fn take_unit(&self) -> Option<Unit> {
+        let mut state = self.state.lock().unwrap();
+        let unit = match state.requeued.pop_front() {
+            Some(unit) => unit,
+            None if state.next_start < self.coordinator.keyspace => {
+                let len = self
+                    .coordinator
+                    .unit_size
+                    .min(self.coordinator.keyspace - state.next_start);
+                state.next_id += 1;
+                let unit = Unit {
+                    id: state.next_id,
+                    start: state.next_start,
+                    len,
+                };
+                state.next_start += len;
+                unit
+            }
+            None => return None,
+        };
+        state.in_flight.insert(unit.id, (unit, 0));
+        Some(unit)
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.state.lock().unwrap().stopped
+    }
+
+    fn update(&self, update: impl FnOnce(&mut State)) {
+        update(&mut self.state.lock().unwrap());
+        self.changed.notify_all();
+    }
+}
+
+/// 分布式爆破的协调端
+pub struct Coordinator {
+    listener: TcpListener,
+    token: String,
+    attack: AttackConfig,
+    verifier: Verifier,
+    keyspace: u128,
+    unit_size: u128,
+    description: String,
+}
+
+impl Coordinator {
+    /// 监听 `addr`，候选空间按每 `unit_size` 个切分为一个单元
+    pub fn bind(addr: &str, token: &str, attack: AttackConfig, unit_size: u128) -> Result<Self> {
+        let verifier = Verifier::new(token)?;
+        // 提前确认攻击配置可以拆分，避免每个工作端各自报错
+        attack.build_slice(0, 0)?;
+        let source = attack.build_source()?;
+        let description = source.describe();
+        let keyspace = source.keyspace().ok_or_else(|| {
+            Error::InvalidConfig(format!("{} 无法预知候选总数，不能拆分", description))
+        })?;
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| Error::Network(format!("无法监听 {}: {}", addr, e)))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| Error::Network(e.to_string()))?;
+        Ok(Self {
+            listener,
+            token: token.to_string(),
+            attack,
+            verifier,
+            keyspace,
+            unit_size: unit_size.max(1),
+            description,
+        })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(|e| Error::Network(e.to_string()))
+    }
+
+    /// 分发工作单元直到找到密钥、所有单元完成或被取消
+    pub fn run<F>(&self, cancel: &CancelToken, on_event: F) -> Result<Option<Found>>
+    where
+        F: Fn(&ClusterEvent) + Sync,
+    {
+        let shared = Shared {
+            coordinator: self,
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+        };
+        let started = Instant::now();
+        let stats = |state: &State| Stats {
+            tried: u64::try_from(state.tried()).unwrap_or(u64::MAX),
+            elapsed: started.elapsed(),
+            sample: state.sample.clone(),
+            keyspace: Some(self.keyspace),
+            offset: 0,
+        };
+        on_event(&ClusterEvent::Engine(Event::Started {
+            description: self.description.clone(),
+            keyspace: Some(self.keyspace),
+            threads: 0,
+        }));
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while !shared.is_stopped() {
+                    match self.listener.accept() {
+                        Ok((stream, peer)) => {
+                            let shared = &shared;
+                            let on_event = &on_event;
+                            scope.spawn(move || serve_worker(stream, peer, shared, on_event));
+                        }
+                        // 非阻塞监听，没有新连接时稍后再试
+                        Err(_) => thread::sleep(POLL),
+                    }
+                }
+            });
+
+            let mut last = Instant::now();
+            let mut state = shared.state.lock().unwrap();
+            loop {
+                if cancel.is_cancelled() {
+                    state.stopped = true;
+                }
+                let exhausted = state.next_start >= self.keyspace
+                    && state.requeued.is_empty()
+                    && state.in_flight.is_empty();
+                if state.found.is_some() || exhausted {
+                    state.stopped = true;
+                }
+                if state.stopped {
+                    break;
+                }
+                if last.elapsed() >= STATS_INTERVAL {
+                    last = Instant::now();
+                    on_event(&ClusterEvent::Engine(Event::Stats(stats(&state))));
+                }
+                state = shared.changed.wait_timeout(state, POLL).unwrap().0;
+            }
+            drop(state);
+            shared.changed.notify_all();
+        });
+
+        let state = shared.state.into_inner().unwrap();
+        let final_stats = stats(&state);
+        let event = match &state.found {
+            Some(found) => Event::Found {
+                found: found.clone(),
+                stats: final_stats,
+            },
+            None if cancel.is_cancelled() => Event::Cancelled(final_stats),
+            None => Event::Finished(final_stats),
+        };
+        on_event(&ClusterEvent::Engine(event));
+        Ok(state.found)
+    }
+}
+
+/// 一个工作端的连接
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    line: Vec<u8>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+            line: Vec::new(),
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)
+    }
+
+    /// 读一条消息，超时返回 `None`，读到一半的行会保留到下次
+    fn recv(&mut self) -> io::Result<Option<Message>> {
+        match self.reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) if self.line.ends_with(b"\n") => {
+                let message = serde_json::from_slice(&self.line);
+                self.line.clear();
+                Ok(Some(message?))
+            }
+            Ok(_) => Ok(None),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn serve_worker<F>(stream: TcpStream, peer: SocketAddr, shared: &Shared, on_event: &F)
+where
+    F: Fn(&ClusterEvent) + Sync,
+{
+    let mut current = None;
+    let _ = talk_to_worker(stream, peer, shared, on_event, &mut current);
+    let mut requeued = None;
+    shared.update(|state| {
+        if let Some(unit) = current
+            && state.in_flight.remove(&unit.id).is_some()
+            && !state.stopped
+        {
+            state.requeued.push_back(unit);
+            requeued = Some(unit.id);
+        }
+    });
+    on_event(&ClusterEvent::WorkerLeft { peer, requeued });
+}
+
+/// 和一个工作端通信，`current` 记录它手上还没完成的单元，断开时由调用方收回
+fn talk_to_worker<F>(
+    stream: TcpStream,
+    peer: SocketAddr,
+    shared: &Shared,
+    on_event: &F,
+    current: &mut Option<Unit>,
+) -> io::Result<()>
+where
+    F: Fn(&ClusterEvent) + Sync,
+{
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL))?;
+    let mut conn = Connection::new(stream)?;
+
+    let threads = loop {
+        if shared.is_stopped() {
+            return Ok(());
+        }
+        match conn.recv()? {
+            Some(Message::Hello { threads }) => break threads,
+            Some(_) => return Err(io::Error::new(ErrorKind::InvalidData, "应先发送 hello")),
+            None => {}
+        }
+    };
+    conn.send(&Message::Job {
+        token: shared.coordinator.token.clone(),
+        attack: shared.coordinator.attack.clone(),
+    })?;
+    on_event(&ClusterEvent::WorkerJoined { peer, threads });
+
+    loop {
+        if shared.is_stopped() {
+            return conn.send(&Message::Stop);
+        }
+        if current.is_none() {
+            match shared.take_unit() {
+                Some(unit) => {
+                    conn.send(&Message::Unit {
+                        id: unit.id,
+                        start: unit.start,
+                        len: unit.len,
+                    })?;
+                    *current = Some(unit);
+                }
+                // 暂时没有单元可分配，等其它工作端完成或断开
+                None => {
+                    let state = shared.state.lock().unwrap();
+                    drop(shared.changed.wait_timeout(state, POLL).unwrap());
+                    continue;
+                }
+            }
+        }
+        let Some(unit) = *current else {
+            continue;
+        };
+        match conn.recv()? {
+            Some(Message::Progress {
+                unit: id,
+                tried,
+                sample,
+            }) if id == unit.id => shared.update(|state| {
+                if let Some(entry) = state.in_flight.get_mut(&id) {
+                    entry.1 = tried;
+                }
+                state.sample = sample.into_bytes();
+            }),
+            Some(Message::Done { unit: id, tried }) if id == unit.id => {
+                if u128::from(tried) != unit.len {
+                    on_event(&ClusterEvent::UnitMismatch {
+                        peer,
+                        unit: id,
+                        tried,
+                        expected: unit.len,
+                    });
+                }
+                *current = None;
+                shared.update(|state| {
+                    state.in_flight.remove(&id);
+                    state.completed += unit.len;
+                });
+            }
+            // 不相信工作端的结果，重新校验一次
+            Some(Message::Found { unit: id, secret }) if id == unit.id => {
+                let coordinator = &shared.coordinator;
+                if let Some(encoding) = coordinator
+                    .verifier
+                    .verify_encoded(&secret, &coordinator.attack.key_encodings)
+                {
+                    shared.update(|state| {
+                        state.found = Some(Found { secret, encoding });
+                        state.stopped = true;
+                    });
+                }
+            }
+            Some(_) => return Err(io::Error::new(ErrorKind::InvalidData, "无效的消息")),
+            None => {}
+        }
+    }
+}
+
+/// 连接协调端并处理它分配的单元，直到协调端发送 `stop`、断开或 `cancel` 被取消
+///
+/// `options` 只影响本机，例如线程数和优先级
+pub fn run_worker<F>(
+    addr: &str,
+    options: RunOptions,
+    cancel: &CancelToken,
+    on_event: F,
+) -> Result<Option<Found>>
+where
+    F: Fn(&WorkerEvent) + Sync,
+{
+    let network = |e: io::Error| Error::Network(format!("{}: {}", addr, e));
+    let stream = TcpStream::connect(addr).map_err(network)?;
+    let mut conn = Connection::new(stream.try_clone().map_err(network)?).map_err(network)?;
+    let threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    conn.send(&Message::Hello { threads }).map_err(network)?;
+
+    let (tx, rx) = unbounded();
+    let writer = Mutex::new(conn.writer.try_clone().map_err(network)?);
+    let send = |message: &Message| -> Result<()> {
+        let mut line = serde_json::to_vec(message).map_err(|source| Error::Json {
+            segment: "消息",
+            source,
+        })?;
+        line.push(b'\n');
+        writer.lock().unwrap().write_all(&line).map_err(network)
+    };
+
+    thread::scope(|scope| {
+        // 单独的线程读消息，这样运行中也能收到 stop
+        let reader_cancel = cancel.clone();
+        scope.spawn(move || {
+            loop {
+                match conn.recv() {
+                    Ok(Some(Message::Stop)) | Err(_) => break,
+                    Ok(Some(message)) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                }
+            }
+            reader_cancel.cancel();
+        });
+
+        let result = (|| {
+            let Ok(Message::Job { token, attack }) = rx.recv() else {
+                return Ok(None);
+            };
+            let cracker = Cracker::new(&token)?
+                .options(options)
+                .key_encodings(attack.key_encodings.clone());
+            on_event(&WorkerEvent::Connected {
+                description: attack.build_source()?.describe(),
+            });
+            while let Ok(message) = rx.recv() {
+                let Message::Unit { id, start, len } = message else {
+                    continue;
+                };
+                on_event(&WorkerEvent::UnitStarted { id, start, len });
+                let source = attack.build_slice(start, len)?;
+                let tried = Mutex::new(0);
+                let found = cracker.run(source, cancel, |event| {
+                    if let Some(stats) = event.stats() {
+                        *tried.lock().unwrap() = stats.tried;
+                        // 发送失败说明连接已断开，读线程会负责停止
+                        let _ = send(&Message::Progress {
+                            unit: id,
+                            tried: stats.tried,
+                            sample: stats.sample_lossy(),
+                        });
+                    }
+                })?;
+                let tried = tried.into_inner().unwrap();
+                if let Some(found) = found {
+                    send(&Message::Found {
+                        unit: id,
+                        secret: found.secret.clone(),
+                    })?;
+                    on_event(&WorkerEvent::Found(found.clone()));
+                    return Ok(Some(found));
+                }
+                if cancel.is_cancelled() {
+                    break;
+                }
+                send(&Message::Done { unit: id, tried })?;
+                on_event(&WorkerEvent::UnitFinished { id, tried });
+            }
+            Ok(None)
+        })();
+        // 关闭连接让读线程退出
+        let _ = stream.shutdown(Shutdown::Both);
+        result
+    })
+}
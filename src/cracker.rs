@@ -0,0 +1,503 @@
+use crate::cancel::CancelToken;
+use crate::error::{Error, Result};
+use crate::event::{Event, Stats};
+use crate::source::CandidateSource;
+use crate::utils::jwt::{KeyEncoding, Verifier};
+use crate::utils::time::option_duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 每个线程一次从来源中取出的候选数量
+const BATCH_SIZE: usize = 1024;
+
+/// 默认的进度发布间隔
+pub const STATS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 监控线程检查是否结束的间隔
+const MONITOR_TICK: Duration = Duration::from_millis(10);
+
+/// 每个线程独占一条缓存行的计数器，避免伪共享
+#[derive(Default)]
+#[repr(align(64))]
+struct Counter(AtomicU64);
+
+/// 爆破成功的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found {
+    /// 完整的候选（含前后缀）
+    pub secret: Vec<u8>,
+    /// 候选是按哪种方式解读后匹配的
+    pub encoding: KeyEncoding,
+}
+
+impl Found {
+    /// 实际的HMAC密钥，即按 `encoding` 解码后的候选
+    pub fn key(&self) -> Vec<u8> {
+        self.encoding
+            .decode(&self.secret)
+            .unwrap_or_else(|| self.secret.clone())
+    }
+
+    /// 按UTF-8显示的密钥
+    pub fn secret_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.secret).to_string()
+    }
+
+    /// 密钥是否可以直接显示，即合法的UTF-8且不含控制字符
+    pub fn is_printable(&self) -> bool {
+        std::str::from_utf8(&self.secret).is_ok_and(|s| !s.chars().any(char::is_control))
+    }
+
+    /// 十六进制表示的密钥
+    pub fn secret_hex(&self) -> String {
+        self.secret.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// 十六进制表示的实际HMAC密钥
+    pub fn key_hex(&self) -> String {
+        self.key().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// 各种输出中 `secret` 字段的内容
+    pub fn to_json(&self) -> Value {
+        json!({
+            "utf8": self.secret_lossy(),
+            "hex": self.secret_hex(),
+            "encoding": self.encoding.name(),
+            "key_hex": self.key_hex(),
+        })
+    }
+}
+
+/// 一批候选，连续存放以减少分配
+#[derive(Default)]
+struct Batch {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+    scratch: Vec<u8>,
+}
+
+impl Batch {
+    fn fill(&mut self, source: &mut dyn CandidateSource, size: usize, stop: impl Fn() -> bool) {
+        self.data.clear();
+        self.ends.clear();
+        while self.ends.len() < size && !stop() && source.next_candidate(&mut self.scratch) {
+            self.data.extend_from_slice(&self.scratch);
+            self.ends.push(self.data.len());
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn last(&self) -> &[u8] {
+        let start = self.ends.len().checked_sub(2).map_or(0, |i| self.ends[i]);
+        &self.data[start..]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+        starts
+            .zip(self.ends.iter().copied())
+            .map(|(start, end)| &self.data[start..end])
+    }
+}
+
+/// 每次爆破的线程设置和工作量上限
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunOptions {
+    /// 线程数，`None` 表示使用全部核心
+    pub threads: Option<usize>,
+    /// 以最低优先级运行，避免影响机器上的其它任务
+    pub low_priority: bool,
+    /// 把每个工作线程绑定到一个CPU核心
+    pub pin_cores: bool,
+    /// 运行时间上限
+    #[serde(with = "option_duration")]
+    pub max_time: Option<Duration>,
+    /// 本次最多尝试的候选数量
+    pub max_candidates: Option<u64>,
+    /// 跳过来源中前面这么多个候选，用于从上次停下的位置继续
+    #[serde(skip)]
+    pub skip: u128,
+}
+
+/// 爆破引擎：持有解析好的令牌，候选密钥来自任意 [`CandidateSource`]
+pub struct Cracker {
+    verifier: Verifier,
+    stats_interval: Duration,
+    options: RunOptions,
+    key_encodings: Vec<KeyEncoding>,
+}
+
+impl Cracker {
+    /// 令牌格式错误或算法不是HS256/384/512时返回错误
+    pub fn new(token: &str) -> Result<Self> {
+        Ok(Self {
+            verifier: Verifier::new(token)?,
+            stats_interval: STATS_INTERVAL,
+            options: RunOptions::default(),
+            key_encodings: Vec::new(),
+        })
+    }
+
+    /// 设置线程数、优先级和核心绑定
+    pub fn options(mut self, options: RunOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 候选的解读方式，通常取自 [`AttackConfig::key_encodings`](crate::AttackConfig)
+    pub fn key_encodings(mut self, encodings: Vec<KeyEncoding>) -> Self {
+        self.key_encodings = encodings;
+        self
+    }
+
+    /// 按 [`RunOptions`] 为这次爆破单独创建线程池
+    fn build_pool(&self) -> Result<rayon::ThreadPool> {
+        let low_priority = self.options.low_priority;
+        let cores = if self.options.pin_cores {
+            core_affinity::get_core_ids().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.options.threads.unwrap_or(0))
+            .thread_name(|index| format!("jwt-cracker-{}", index))
+            .start_handler(move |index| {
+                if low_priority {
+                    let _ = thread_priority::set_current_thread_priority(
+                        thread_priority::ThreadPriority::Min,
+                    );
+                }
+                if !cores.is_empty() {
+                    core_affinity::set_for_current(cores[index % cores.len()]);
+                }
+            })
+            .build()
+            .map_err(|e| Error::InvalidConfig(format!("无法创建线程池: {}", e)))
+    }
+
+    /// 设置 [`Event::Stats`] 的发布间隔
+    pub fn stats_interval(mut self, interval: Duration) -> Self {
+        self.stats_interval = interval;
+        self
+    }
+
+    pub fn verifier(&self) -> &Verifier {
+        &self.verifier
+    }
+
+    /// 在专用线程池的每个线程上分批取出候选并校验，直到找到密钥、候选耗尽或 `cancel` 被触发。
+    ///
+    /// 各线程只累加自己的计数器，由单独的监控线程汇总后按间隔调用 `on_event`。
+    /// 取消或达到上限后不再取新的批次，已经取出的批次会校验完，因此结束事件中的
+    /// [`Stats::position`] 正好是来源中已消耗的候选数，可以作为下次的 `skip` 继续。
+    pub fn run<S, F>(&self, source: S, cancel: &CancelToken, on_event: F) -> Result<Option<Found>>
+    where
+        S: CandidateSource,
+        F: Fn(&Event) + Sync,
+    {
+        let mut source = source;
+        let skip = self.options.skip;
+        if skip > 0 && !source.seek(skip) {
+            return Err(Error::InvalidConfig(format!(
+                "{} 不支持从中间继续",
+                source.describe()
+            )));
+        }
+        let pool = self.build_pool()?;
+        let started = Instant::now();
+        let keyspace = source.keyspace();
+        let threads = pool.current_num_threads();
+        on_event(&Event::Started {
+            description: source.describe(),
+            keyspace,
+            threads,
+        });
+
+        // 来源和已经取出的候选数放在同一把锁里，保证数量上限是精确的
+        let feed = Mutex::new((source, 0u64));
+        let found = Mutex::new(None);
+        let done = AtomicBool::new(false);
+        let limit_reached = AtomicBool::new(false);
+        let stopping = || cancel.is_cancelled() || limit_reached.load(Ordering::Relaxed);
+        let counters: Vec<Counter> = (0..threads).map(|_| Counter::default()).collect();
+        let sample = Mutex::new(Vec::new());
+        let snapshot = || Stats {
+            tried: counters.iter().map(|c| c.0.load(Ordering::Relaxed)).sum(),
+            elapsed: started.elapsed(),
+            sample: sample.lock().unwrap().clone(),
+            keyspace,
+            offset: skip,
+        };
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut last = Instant::now();
+                while !done.load(Ordering::Relaxed) {
+                    thread::sleep(MONITOR_TICK);
+                    if let Some(max_time) = self.options.max_time
+                        && started.elapsed() >= max_time
+                    {
+                        limit_reached.store(true, Ordering::Relaxed);
+                    }
+                    if last.elapsed() >= self.stats_interval {
+                        on_event(&Event::Stats(snapshot()));
+                        last = Instant::now();
+                    }
+                }
+            });
+
+            pool.broadcast(|ctx| {
+                let mut batch = Batch::default();
+                while !done.load(Ordering::Relaxed) && !stopping() {
+                    {
+                        let (source, pulled) = &mut *feed.lock().unwrap();
+                        let size = match self.options.max_candidates {
+                            Some(max) => BATCH_SIZE.min(max.saturating_sub(*pulled) as usize),
+                            None => BATCH_SIZE,
+                        };
+                        if size == 0 {
+                            limit_reached.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        batch.fill(source, size, stopping);
+                        *pulled += batch.len() as u64;
+                    }
+                    if batch.len() == 0 {
+                        break;
+                    }
+                    let matched = batch.iter().find_map(|candidate| {
+                        self.verifier
+                            .verify_encoded(candidate, &self.key_encodings)
+                            .map(|encoding| Found {
+                                secret: candidate.to_vec(),
+                                encoding,
+                            })
+                    });
+                    if let Some(matched) = matched {
+                        *found.lock().unwrap() = Some(matched);
+                        done.store(true, Ordering::Relaxed);
+                    }
+                    counters[ctx.index()]
+                        .0
+                        .fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    if let Ok(mut sample) = sample.try_lock() {
+                        sample.clear();
+                        sample.extend_from_slice(batch.last());
+                    }
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+        });
+
+        let stats = snapshot();
+        let found = found.into_inner().unwrap();
+        let event = match &found {
+            Some(found) => Event::Found {
+                found: found.clone(),
+                stats,
+            },
+            None if cancel.is_cancelled() => Event::Cancelled(stats),
+            None if limit_reached.load(Ordering::Relaxed) => Event::LimitReached(stats),
+            None => Event::Finished(stats),
+        };
+        on_event(&event);
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Mask;
+    use crate::source::testing::collect;
+    use crate::utils::jwt::{HmacAlgorithm, sign_hmac};
+    use std::sync::Arc;
+
+    /// 记下真正从来源中取出的每个候选
+    struct Recording {
+        inner: Mask,
+        seen: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl CandidateSource for Recording {
+        fn keyspace(&self) -> Option<u128> {
+            self.inner.keyspace()
+        }
+
+        fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+            let more = self.inner.next_candidate(buf);
+            if more {
+                self.seen.lock().unwrap().push(buf.clone());
+            }
+            more
+        }
+
+        fn seek(&mut self, position: u128) -> bool {
+            self.inner.seek(position)
+        }
+
+        fn describe(&self) -> String {
+            self.inner.describe()
+        }
+    }
+
+    fn token(secret: &[u8]) -> String {
+        sign_hmac(
+            &json!({ "typ": "JWT" }),
+            &json!({ "sub": "test" }),
+            HmacAlgorithm::HS256,
+            secret,
+        )
+    }
+
+    fn digits() -> Mask {
+        Mask::parse("?d?d?d?d", &[]).unwrap()
+    }
+
+    /// 运行一次并返回结果、全部事件和取出的候选
+    fn run(
+        token: &str,
+        options: RunOptions,
+        cancel: &CancelToken,
+    ) -> (Option<Found>, Vec<Event>, Vec<Vec<u8>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let source = Recording {
+            inner: digits(),
+            seen: seen.clone(),
+        };
+        let events = Mutex::new(Vec::new());
+        let found = Cracker::new(token)
+            .unwrap()
+            .options(options)
+            .stats_interval(Duration::from_millis(1))
+            .run(source, cancel, |event| {
+                events.lock().unwrap().push(event.clone())
+            })
+            .unwrap();
+        let seen = seen.lock().unwrap().clone();
+        (found, events.into_inner().unwrap(), seen)
+    }
+
+    fn last_stats(events: &[Event]) -> &Stats {
+        events.last().unwrap().stats().unwrap()
+    }
+
+    #[test]
+    fn max_candidates_is_exact_and_resumes_without_gaps() {
+        let all = collect(&mut digits());
+        let missing = token(b"not a digit");
+        let options = RunOptions {
+            threads: Some(4),
+            max_candidates: Some(1234),
+            ..RunOptions::default()
+        };
+        let (found, events, first) = run(&missing, options.clone(), &CancelToken::new());
+        assert_eq!(found, None);
+        assert!(matches!(events.last(), Some(Event::LimitReached(_))));
+        assert_eq!(last_stats(&events).tried, 1234);
+        assert_eq!(last_stats(&events).position(), 1234);
+        assert_eq!(first, all[..1234]);
+
+        let options = RunOptions {
+            max_candidates: None,
+            skip: 1234,
+            ..options
+        };
+        let (found, events, rest) = run(&missing, options.clone(), &CancelToken::new());
+        assert_eq!(found, None);
+        assert!(matches!(events.last(), Some(Event::Finished(_))));
+        assert_eq!(last_stats(&events).tried, 10_000 - 1234);
+        assert_eq!(last_stats(&events).position(), 10_000);
+        assert_eq!(rest, all[1234..]);
+
+        // 继续时第一个尝试的就是第 N+1 个候选
+        let options = RunOptions {
+            max_candidates: Some(1),
+            ..options
+        };
+        let (found, _, tried) = run(&token(&all[1234]), options, &CancelToken::new());
+        assert_eq!(found.unwrap().secret, all[1234]);
+        assert_eq!(tried, [all[1234].clone()]);
+    }
+
+    #[test]
+    fn max_time_stops_with_limit_reached() {
+        let source = Mask::parse("?a?a?a?a?a?a?a?a", &[]).unwrap();
+        let options = RunOptions {
+            threads: Some(2),
+            max_time: Some(Duration::from_millis(200)),
+            ..RunOptions::default()
+        };
+        let events = Mutex::new(Vec::new());
+        let started = Instant::now();
+        let found = Cracker::new(&token(b"not in the mask"))
+            .unwrap()
+            .options(options)
+            .run(source, &CancelToken::new(), |event| {
+                events.lock().unwrap().push(event.clone())
+            })
+            .unwrap();
+        assert_eq!(found, None);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        let events = events.into_inner().unwrap();
+        let Some(Event::LimitReached(stats)) = events.last() else {
+            panic!("应该以 LimitReached 结束: {:?}", events.last());
+        };
+        assert!(stats.tried > 0);
+    }
+
+    /// `Started` 在最前，中间只有 `Stats`，最后一个是结束事件；返回结束事件
+    fn check_order(events: &[Event]) -> &Event {
+        let (last, rest) = events.split_last().unwrap();
+        let Some((Event::Started { keyspace, .. }, stats)) = rest.split_first() else {
+            panic!("第一个事件应该是 Started: {:?}", rest.first());
+        };
+        assert_eq!(*keyspace, Some(10_000));
+        let mut tried = 0;
+        for event in stats {
+            let Event::Stats(stats) = event else {
+                panic!("中间应该只有 Stats: {:?}", event);
+            };
+            assert!(stats.tried >= tried);
+            tried = stats.tried;
+        }
+        assert!(last.stats().unwrap().tried >= tried);
+        last
+    }
+
+    #[test]
+    fn events_arrive_in_order() {
+        let options = RunOptions {
+            threads: Some(2),
+            ..RunOptions::default()
+        };
+        let (_, events, _) = run(&token(b"not a digit"), options.clone(), &CancelToken::new());
+        let Event::Finished(stats) = check_order(&events) else {
+            panic!("候选耗尽时应该以 Finished 结束");
+        };
+        assert_eq!(stats.tried, 10_000);
+        assert_eq!(stats.percent(), Some(100.0));
+
+        let (found, events, _) = run(&token(b"4711"), options.clone(), &CancelToken::new());
+        let Event::Found { found: event, .. } = check_order(&events) else {
+            panic!("找到密钥时应该以 Found 结束");
+        };
+        assert_eq!(Some(event), found.as_ref());
+        assert_eq!(event.secret, b"4711");
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let (found, events, _) = run(&token(b"4711"), options, &cancel);
+        assert_eq!(found, None);
+        assert!(matches!(check_order(&events), Event::Cancelled(_)));
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// 库中所有可失败操作的错误
+#[derive(Debug)]
+pub enum Error {
+    /// 令牌不是由 `.` 分隔的三段
+    MalformedToken { parts: usize },
+    /// 某一段不是合法的 Base64URL
+    Base64 {
+        segment: &'static str,
+        source: base64::DecodeError,
+    },
+    /// 某一段不是合法的 JSON
+    Json {
+        segment: &'static str,
+        source: serde_json::Error,
+    },
+    /// 某个文件不是合法的 TOML
+    Toml {
+        segment: &'static str,
+        source: toml::de::Error,
+    },
+    /// Header 中没有 `alg`
+    MissingAlgorithm,
+    /// 当前操作不支持这个算法
+    UnsupportedAlgorithm(String),
+    /// 读写文件失败
+    Io { path: PathBuf, source: io::Error },
+    /// 攻击配置有误，例如掩码、规则或字符集
+    InvalidConfig(String),
+    /// 生成密钥或签名失败
+    Crypto(String),
+    /// 监听端口或网络通信失败
+    Network(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MalformedToken { parts } => {
+                write!(f, "JWT格式错误: 应为3段，实际为{}段", parts)
+            }
+            Error::Base64 { segment, source } => {
+                write!(f, "{} 不是合法的 Base64URL: {}", segment, source)
+            }
+            Error::Json { segment, source } => write!(f, "{} 不是合法的 JSON: {}", segment, source),
+            Error::Toml { segment, source } => write!(f, "{} 不是合法的 TOML: {}", segment, source),
+            Error::MissingAlgorithm => write!(f, "Header 中缺少 alg"),
+            Error::UnsupportedAlgorithm(alg) => write!(f, "不支持的算法: {}", alg),
+            Error::Io { path, source } => write!(f, "无法读写 {}: {}", path.display(), source),
+            Error::InvalidConfig(message) => write!(f, "攻击配置有误: {}", message),
+            Error::Crypto(message) => write!(f, "签名失败: {}", message),
+            Error::Network(message) => write!(f, "网络错误: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Base64 { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            Error::Toml { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! 爆破引擎发布的结构化事件，CLI 和 GUI 都通过它们显示进度
+
+use crate::cracker::Found;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// 某一时刻的汇总统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// 所有线程已尝试的候选数量
+    pub tried: u64,
+    /// 从开始到现在经过的时间
+    pub elapsed: Duration,
+    /// 最近尝试过的一个候选
+    pub sample: Vec<u8>,
+    /// 候选总数，无法预知时为 `None`
+    pub keyspace: Option<u128>,
+    /// 本次开始前跳过的候选数，从上次的进度继续时不为 0
+    pub offset: u128,
+}
+
+impl Stats {
+    /// 每秒尝试的候选数量
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.tried as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// 在整个来源中的位置，也就是下次继续时要跳过的候选数
+    pub fn position(&self) -> u128 {
+        self.offset + u128::from(self.tried)
+    }
+
+    /// 完成的百分比，总数未知时返回 `None`
+    pub fn percent(&self) -> Option<f64> {
+        match self.keyspace {
+            Some(0) => Some(100.0),
+            Some(total) => Some((self.position() as f64 / total as f64 * 100.0).min(100.0)),
+            None => None,
+        }
+    }
+
+    /// 按UTF-8显示的当前候选
+    pub fn sample_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.sample).to_string()
+    }
+
+    /// JSON 格式的统计信息
+    pub fn to_json(&self) -> Value {
+        json!({
+            "tried": self.tried,
+            "position": keyspace_json(self.position()),
+            "keyspace": self.keyspace.map(keyspace_json),
+            "percent": self.percent(),
+            "duration_secs": self.elapsed.as_secs_f64(),
+            "rate": self.rate(),
+            "sample": self.sample_lossy(),
+        })
+    }
+}
+
+/// 一次爆破过程中的事件，最后一个事件一定是 `Found`、`Finished`、`Cancelled` 或 `LimitReached` 之一
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// 开始爆破
+    Started {
+        description: String,
+        keyspace: Option<u128>,
+        threads: usize,
+    },
+    /// 按固定间隔发布的进度
+    Stats(Stats),
+    /// 找到了密钥
+    Found { found: Found, stats: Stats },
+    /// 所有候选都已尝试完
+    Finished(Stats),
+    /// 被提前停止
+    Cancelled(Stats),
+    /// 达到时间或候选数量上限
+    LimitReached(Stats),
+}
+
+impl Event {
+    /// 事件携带的统计信息
+    pub fn stats(&self) -> Option<&Stats> {
+        match self {
+            Event::Started { .. } => None,
+            Event::Stats(stats)
+            | Event::Found { stats, .. }
+            | Event::Finished(stats)
+            | Event::Cancelled(stats)
+            | Event::LimitReached(stats) => Some(stats),
+        }
+    }
+
+    /// 事件名，也是 JSON 中 `event` 字段的值
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Started { .. } => "started",
+            Event::Stats(_) => "stats",
+            Event::Found { .. } => "found",
+            Event::Finished(_) => "finished",
+            Event::Cancelled(_) => "cancelled",
+            Event::LimitReached(_) => "limit_reached",
+        }
+    }
+
+    /// 单行 JSON 表示，适合逐行输出
+    pub fn to_json(&self) -> Value {
+        let mut value = match self {
+            Event::Started {
+                description,
+                keyspace,
+                threads,
+            } => json!({
+                "description": description,
+                "keyspace": keyspace.map(keyspace_json),
+                "threads": threads,
+            }),
+            Event::Found { found, stats } => {
+                let mut value = stats.to_json();
+                value["secret"] = found.to_json();
+                value
+            }
+            Event::Stats(stats)
+            | Event::Finished(stats)
+            | Event::Cancelled(stats)
+            | Event::LimitReached(stats) => stats.to_json(),
+        };
+        value["event"] = json!(self.name());
+        value
+    }
+}
+
+/// 超出 u64 的候选数以字符串表示，避免 JSON 解析端丢精度
+fn keyspace_json(count: u128) -> Value {
+    match u64::try_from(count) {
+        Ok(count) => json!(count),
+        Err(_) => json!(count.to_string()),
+    }
+}
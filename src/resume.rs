@@ -0,0 +1,77 @@
+//! 爆破进度文件，记录令牌、攻击配置和停下时的位置，下次可以从这里继续
+
+use crate::attack::AttackConfig;
+use crate::error::{Error, Result};
+use crate::utils::time::now_secs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 保存到进度文件中的内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeState {
+    pub token: String,
+    pub config: AttackConfig,
+    /// 来源中已经尝试过的候选数，继续时作为 `RunOptions::skip`
+    pub position: u128,
+    /// 保存时的Unix时间戳
+    pub saved_at: u64,
+}
+
+impl ResumeState {
+    pub fn new(token: &str, config: AttackConfig, position: u128) -> Self {
+        Self {
+            token: token.to_string(),
+            config,
+            position,
+            saved_at: now_secs(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        serde_json::from_str(&text).map_err(|source| Error::Json {
+            segment: "进度文件",
+            source,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = serde_json::to_string_pretty(self).map_err(|source| Error::Json {
+            segment: "进度文件",
+            source,
+        })?;
+        fs::write(path, text).map_err(|e| Error::io(path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attack::AttackMode;
+
+    #[test]
+    fn round_trip() {
+        let config = AttackConfig {
+            stages: vec![
+                AttackMode::Common,
+                AttackMode::Mask {
+                    mask: "?d?d".to_string(),
+                    custom_charsets: Vec::new(),
+                },
+            ],
+            suffix: "!".to_string(),
+            ..AttackConfig::default()
+        };
+        // 超出 u64 的位置也要能原样读回
+        let state = ResumeState::new("a.b.c", config, u128::from(u64::MAX) + 7);
+        let path =
+            std::env::temp_dir().join(format!("jwt_cracker_{}_resume.json", std::process::id()));
+        state.save(&path).unwrap();
+        let loaded = ResumeState::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded, state);
+    }
+}
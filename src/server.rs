@@ -0,0 +1,434 @@
+//! 本地 HTTP 服务，让其它工具不用调用命令行也能提交破解任务
+//!
+//! | 方法 | 路径 | 说明 |
+//! |--------|--------------|----------------------------------|
+//! | POST   | `/jobs`      | 提交任务，请求体为 [`JobRequest`] |
+//! | GET    | `/jobs`      | 列出所有任务及结果               |
+//! | GET    | `/jobs/{id}` | 查询任务状态和进度               |
+//! | DELETE | `/jobs/{id}` | 取消排队中或正在运行的任务       |
+//! | GET    | `/events`    | SSE 事件流，任务状态变化时推送   |
+
+use crate::attack::{AttackConfig, AttackMode};
+use crate::cancel::CancelToken;
+use crate::cracker::{Cracker, Found, RunOptions};
+use crate::error::{Error, Result};
+use crate::event::{Event, Stats};
+use crate::utils::time::now_secs;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TrySendError, bounded, unbounded};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+
+/// 请求体的大小上限
+const MAX_BODY: u64 = 1 << 20;
+/// 没有事件时每隔这么久发送一次注释行，及时发现断开的 SSE 连接
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// 提交任务的请求体，`attack` 和 `options` 与任务文件中的格式相同
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobRequest {
+    pub token: String,
+    #[serde(default)]
+    pub attack: AttackConfig,
+    #[serde(default)]
+    pub options: RunOptions,
+}
+
+/// 服务的监听地址和容量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeOptions {
+    pub addr: String,
+    /// 排队任务的上限，队列满时提交会返回 503
+    pub queue_size: usize,
+    /// 同时运行的任务数
+    pub workers: usize,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:8087".to_string(),
+            queue_size: 16,
+            workers: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobStatus {
+    Queued,
+    Running,
+    Found,
+    Exhausted,
+    Cancelled,
+    LimitReached,
+    Failed,
+}
+
+impl JobStatus {
+    fn name(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Found => "found",
+            JobStatus::Exhausted => "exhausted",
+            JobStatus::Cancelled => "cancelled",
+            JobStatus::LimitReached => "limit_reached",
+            JobStatus::Failed => "error",
+        }
+    }
+}
+
+struct JobEntry {
+    id: u64,
+    token: String,
+    status: JobStatus,
+    stats: Option<Stats>,
+    found: Option<Found>,
+    error: Option<String>,
+    cancel: CancelToken,
+    submitted_at: u64,
+}
+
+impl JobEntry {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "status": self.status.name(),
+            "token": self.token,
+            "submitted_at": self.submitted_at,
+            "stats": self.stats.as_ref().map(Stats::to_json),
+            "secret": self.found.as_ref().map(Found::to_json),
+            "error": self.error,
+        })
+    }
+}
+
+/// 所有连接和工作线程共享的状态
+#[derive(Default)]
+struct Shared {
+    jobs: Mutex<BTreeMap<u64, JobEntry>>,
+    requests: Mutex<BTreeMap<u64, JobRequest>>,
+    next_id: AtomicU64,
+    subscribers: Mutex<Vec<Sender<(&'static str, Value)>>>,
+}
+
+impl Shared {
+    /// 推送给所有 SSE 连接，顺便清理已经断开的
+    fn publish(&self, event: &'static str, data: Value) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send((event, data.clone())).is_ok());
+    }
+
+    fn job_json(&self, id: u64) -> Option<Value> {
+        self.jobs.lock().unwrap().get(&id).map(JobEntry::to_json)
+    }
+
+    /// 修改任务状态并推送给订阅者
+    fn set_status(&self, id: u64, status: JobStatus, update: impl FnOnce(&mut JobEntry)) {
+        let data = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(&id) else {
+                return;
+            };
+            job.status = status;
+            update(job);
+            job.to_json()
+        };
+        self.publish(status.name(), data);
+    }
+
+    /// 把排队中的任务标记为运行中，已经取消的返回 `None`
+    fn start(&self, id: u64) -> Option<CancelToken> {
+        let (cancel, data) = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs.get_mut(&id)?;
+            if job.status != JobStatus::Queued {
+                return None;
+            }
+            job.status = JobStatus::Running;
+            (job.cancel.clone(), job.to_json())
+        };
+        self.publish(JobStatus::Running.name(), data);
+        Some(cancel)
+    }
+
+    /// 取消任务，排队中的直接标记为已取消，运行中的由工作线程在停下后更新状态
+    fn cancel(&self, id: u64) -> Option<Value> {
+        let (queued, data) = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs.get_mut(&id)?;
+            job.cancel.cancel();
+            let queued = job.status == JobStatus::Queued;
+            if queued {
+                job.status = JobStatus::Cancelled;
+            }
+            (queued, job.to_json())
+        };
+        if queued {
+            self.publish(JobStatus::Cancelled.name(), data.clone());
+        }
+        Some(data)
+    }
+
+    fn cancel_all(&self) {
+        for job in self.jobs.lock().unwrap().values() {
+            job.cancel.cancel();
+        }
+    }
+}
+
+/// 停止 [`Server::run`]，可以在其它线程（例如 Ctrl-C 处理函数）中调用
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    http: Arc<tiny_http::Server>,
+}
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.http.unblock();
+    }
+}
+
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    shared: Arc<Shared>,
+    queue: Sender<u64>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Server {
+    /// 监听地址并启动工作线程，端口为 0 时由系统分配
+    pub fn bind(options: &ServeOptions) -> Result<Self> {
+        let http = tiny_http::Server::http(&options.addr)
+            .map_err(|e| Error::Network(format!("无法监听 {}: {}", options.addr, e)))?;
+        let shared = Arc::new(Shared::default());
+        let (queue, jobs) = bounded(options.queue_size.max(1));
+        let workers = (0..options.workers.max(1))
+            .map(|_| {
+                let shared = shared.clone();
+                let jobs = jobs.clone();
+                thread::spawn(move || worker(&shared, jobs))
+            })
+            .collect();
+        Ok(Self {
+            http: Arc::new(http),
+            shared,
+            queue,
+            workers,
+        })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            http: self.http.clone(),
+        }
+    }
+
+    /// 处理请求直到 [`ShutdownHandle::shutdown`]，之后取消所有任务并等待工作线程退出
+    pub fn run(self) -> Result<()> {
+        for request in self.http.incoming_requests() {
+            let shared = self.shared.clone();
+            let queue = self.queue.clone();
+            // SSE 连接会一直占着线程，每个请求单独一个线程
+            thread::spawn(move || handle(request, &shared, &queue));
+        }
+        self.shared.cancel_all();
+        self.shared.subscribers.lock().unwrap().clear();
+        drop(self.queue);
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+        Ok(())
+    }
+}
+
+fn worker(shared: &Shared, jobs: Receiver<u64>) {
+    for id in jobs {
+        let request = shared.requests.lock().unwrap().remove(&id);
+        let (Some(request), Some(cancel)) = (request, shared.start(id)) else {
+            continue;
+        };
+        match crack(shared, id, &request, &cancel) {
+            Ok((status, found)) => shared.set_status(id, status, |job| job.found = found),
+            Err(e) => {
+                shared.set_status(id, JobStatus::Failed, |job| job.error = Some(e.to_string()))
+            }
+        }
+    }
+}
+
+fn crack(
+    shared: &Shared,
+    id: u64,
+    request: &JobRequest,
+    cancel: &CancelToken,
+) -> Result<(JobStatus, Option<Found>)> {
+    let cracker = Cracker::new(&request.token)?
+        .options(request.options.clone())
+        .key_encodings(request.attack.key_encodings.clone());
+    let source = request.attack.build_source()?;
+    let status = Mutex::new(JobStatus::Exhausted);
+    let found = cracker.run(source, cancel, |event| {
+        if let Some(stats) = event.stats()
+            && let Some(job) = shared.jobs.lock().unwrap().get_mut(&id)
+        {
+            job.stats = Some(stats.clone());
+        }
+        match event {
+            Event::Found { .. } => *status.lock().unwrap() = JobStatus::Found,
+            Event::Cancelled(_) => *status.lock().unwrap() = JobStatus::Cancelled,
+            Event::LimitReached(_) => *status.lock().unwrap() = JobStatus::LimitReached,
+            _ => {}
+        }
+    })?;
+    Ok((status.into_inner().unwrap(), found))
+}
+
+fn handle(mut request: Request, shared: &Shared, queue: &Sender<u64>) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let method = request.method().clone();
+    let (status, body) = match (&method, segments.as_slice()) {
+        (Method::Get, ["events"]) => return stream_events(request, shared),
+        (Method::Post, ["jobs"]) => submit(&mut request, shared, queue),
+        (Method::Get, ["jobs"]) => {
+            let jobs: Vec<Value> = shared
+                .jobs
+                .lock()
+                .unwrap()
+                .values()
+                .map(JobEntry::to_json)
+                .collect();
+            (200, json!({ "jobs": jobs }))
+        }
+        (Method::Get, ["jobs", id]) => match id.parse().ok().and_then(|id| shared.job_json(id)) {
+            Some(job) => (200, job),
+            None => error(404, "没有这个任务"),
+        },
+        (Method::Delete, ["jobs", id]) => match id.parse().ok().and_then(|id| shared.cancel(id)) {
+            Some(job) => (202, job),
+            None => error(404, "没有这个任务"),
+        },
+        _ => error(404, "没有这个接口"),
+    };
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json; charset=utf-8").unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(StatusCode(status))
+        .with_header(content_type);
+    let _ = request.respond(response);
+}
+
+fn error(status: u16, message: impl Into<String>) -> (u16, Value) {
+    (status, json!({ "error": message.into() }))
+}
+
+fn submit(request: &mut Request, shared: &Shared, queue: &Sender<u64>) -> (u16, Value) {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+        return error(400, format!("无法读取请求体: {}", e));
+    }
+    let job: JobRequest = match serde_json::from_str(&body) {
+        Ok(job) => job,
+        Err(source) => {
+            let e = Error::Json {
+                segment: "请求体",
+                source,
+            };
+            return error(400, e.to_string());
+        }
+    };
+    // 服务的标准输入不属于任何请求
+    if job.attack.stages.contains(&AttackMode::Stdin) {
+        return error(400, "服务模式不支持从标准输入读取候选");
+    }
+    if let Err(e) = Cracker::new(&job.token) {
+        return error(400, e.to_string());
+    }
+
+    let id = shared.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    shared.jobs.lock().unwrap().insert(
+        id,
+        JobEntry {
+            id,
+            token: job.token.clone(),
+            status: JobStatus::Queued,
+            stats: None,
+            found: None,
+            error: None,
+            cancel: CancelToken::new(),
+            submitted_at: now_secs(),
+        },
+    );
+    shared.requests.lock().unwrap().insert(id, job);
+    let job = shared.job_json(id).unwrap_or_default();
+    // 入队时持有订阅者的锁，保证 queued 事件先于工作线程发出的 running 事件
+    let mut subscribers = shared.subscribers.lock().unwrap();
+    match queue.try_send(id) {
+        Ok(()) => {
+            subscribers.retain(|tx| tx.send((JobStatus::Queued.name(), job.clone())).is_ok());
+            (202, job)
+        }
+        Err(e) => {
+            drop(subscribers);
+            shared.jobs.lock().unwrap().remove(&id);
+            shared.requests.lock().unwrap().remove(&id);
+            match e {
+                TrySendError::Full(_) => error(503, "任务队列已满，请稍后再试"),
+                TrySendError::Disconnected(_) => error(503, "服务正在停止"),
+            }
+        }
+    }
+}
+
+/// 直接写响应，tiny_http 的分块编码会攒满缓冲区才发送，不适合事件流
+fn stream_events(request: Request, shared: &Shared) {
+    let (tx, rx) = unbounded();
+    shared.subscribers.lock().unwrap().push(tx);
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\n\
+        Content-Type: text/event-stream; charset=utf-8\r\n\
+        Cache-Control: no-cache\r\n\
+        Connection: close\r\n\r\n";
+    if writer
+        .write_all(head.as_bytes())
+        .and_then(|_| writer.flush())
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        let chunk = match rx.recv_timeout(KEEPALIVE) {
+            Ok((event, data)) => format!("event: {}\ndata: {}\n\n", event, data),
+            Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if writer
+            .write_all(chunk.as_bytes())
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::source::CandidateSource;
+
+/// 依次耗尽多个来源，例如“常见密钥 → 字典 → 掩码”
+pub struct Chain {
+    sources: Vec<Box<dyn CandidateSource>>,
+    current: usize,
+}
+
+impl Chain {
+    pub fn new(sources: Vec<Box<dyn CandidateSource>>) -> Self {
+        Self {
+            sources,
+            current: 0,
+        }
+    }
+}
+
+impl CandidateSource for Chain {
+    fn keyspace(&self) -> Option<u128> {
+        self.sources
+            .iter()
+            .try_fold(0u128, |total, source| total.checked_add(source.keyspace()?))
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        while let Some(source) = self.sources.get_mut(self.current) {
+            if source.next_candidate(buf) {
+                return true;
+            }
+            self.current += 1;
+        }
+        false
+    }
+
+    fn seek(&mut self, mut position: u128) -> bool {
+        for (index, source) in self.sources.iter_mut().enumerate() {
+            let Some(keyspace) = source.keyspace() else {
+                return false;
+            };
+            if position < keyspace {
+                self.current = index;
+                if !source.seek(position) {
+                    return false;
+                }
+                // 后面的来源可能已经读过一部分，往回跳时要让它们重新开始
+                for later in &mut self.sources[index + 1..] {
+                    later.seek(0);
+                }
+                return true;
+            }
+            position -= keyspace;
+        }
+        self.current = self.sources.len();
+        true
+    }
+
+    fn describe(&self) -> String {
+        self.sources
+            .iter()
+            .map(|source| source.describe())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+}
+
+/// 给每个候选加上固定的前缀和后缀
+pub struct Affix<S> {
+    inner: S,
+    prefix: Vec<u8>,
+    suffix: Vec<u8>,
+}
+
+impl<S: CandidateSource> Affix<S> {
+    pub fn new(inner: S, prefix: &[u8], suffix: &[u8]) -> Self {
+        Self {
+            inner,
+            prefix: prefix.to_vec(),
+            suffix: suffix.to_vec(),
+        }
+    }
+}
+
+impl<S: CandidateSource> CandidateSource for Affix<S> {
+    fn keyspace(&self) -> Option<u128> {
+        self.inner.keyspace()
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.inner.next_candidate(buf) {
+            return false;
+        }
+        if !self.prefix.is_empty() {
+            buf.splice(0..0, self.prefix.iter().copied());
+        }
+        buf.extend_from_slice(&self.suffix);
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        self.inner.seek(position)
+    }
+
+    fn describe(&self) -> String {
+        self.inner.describe()
+    }
+}
+
+/// 来源中从 `start` 开始的 `len` 个候选，用于切分任务
+pub struct Slice<S> {
+    inner: S,
+    start: u128,
+    len: u128,
+    produced: u128,
+}
+
+impl<S: CandidateSource> Slice<S> {
+    /// 来源不支持跳转时返回 `None`
+    pub fn new(mut inner: S, start: u128, len: u128) -> Option<Self> {
+        if !inner.seek(start) {
+            return None;
+        }
+        Some(Self {
+            inner,
+            start,
+            len,
+            produced: 0,
+        })
+    }
+}
+
+impl<S: CandidateSource> CandidateSource for Slice<S> {
+    fn keyspace(&self) -> Option<u128> {
+        Some(self.len)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if self.produced >= self.len || !self.inner.next_candidate(buf) {
+            return false;
+        }
+        self.produced += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        let position = position.min(self.len);
+        self.produced = position;
+        self.inner.seek(self.start + position)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} [{}..{}]",
+            self.inner.describe(),
+            self.start,
+            self.start + self.len
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+    use crate::source::{Mask, Wordlist};
+
+    fn chain() -> Chain {
+        Chain::new(vec![
+            Box::new(Wordlist::from_words(["secret", "admin"])),
+            Box::new(Wordlist::from_words(Vec::<&str>::new())),
+            Box::new(Mask::parse("?d?d", &[]).unwrap()),
+        ])
+    }
+
+    #[test]
+    fn chain_seek_matches_iteration() {
+        let all = check_seek(chain);
+        assert_eq!(all.len(), 102);
+        assert_eq!(all[2], b"00");
+    }
+
+    #[test]
+    fn affix_and_slice() {
+        let all = check_seek(|| Affix::new(chain(), b"<", b">"));
+        assert_eq!(all[0], b"<secret>");
+        let sliced = check_seek(|| Slice::new(chain(), 1, 3).unwrap());
+        let expected: Vec<&[u8]> = vec![b"admin", b"00", b"01"];
+        assert_eq!(sliced, expected);
+    }
+}
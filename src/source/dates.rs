@@ -0,0 +1,204 @@
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use crate::utils::time::{civil_from_days, days_from_civil};
+
+/// 未指定格式时使用的日期格式
+pub const DEFAULT_DATE_FORMATS: [&str; 9] = [
+    "YYYYMMDD",
+    "DDMMYYYY",
+    "MMDDYYYY",
+    "YYMMDD",
+    "DDMMYY",
+    "YYYY-MM-DD",
+    "DD-MM-YYYY",
+    "DD.MM.YYYY",
+    "DD/MM/YYYY",
+];
+
+/// 日期格式中的一段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// 四位年份
+    Year,
+    /// 两位年份
+    ShortYear,
+    /// 两位月份
+    Month,
+    /// 不补零的月份
+    ShortMonth,
+    /// 两位日期
+    Day,
+    /// 不补零的日期
+    ShortDay,
+    Literal(char),
+}
+
+/// 解析日期格式，`YYYY`、`YY`、`MM`、`M`、`DD`、`D` 为占位符，其余字符原样输出
+fn parse_format(format: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = match c {
+            'Y' if rest.starts_with("YYYY") => (Token::Year, 4),
+            'Y' if rest.starts_with("YY") => (Token::ShortYear, 2),
+            'M' if rest.starts_with("MM") => (Token::Month, 2),
+            'M' => (Token::ShortMonth, 1),
+            'D' if rest.starts_with("DD") => (Token::Day, 2),
+            'D' => (Token::ShortDay, 1),
+            _ => (Token::Literal(c), c.len_utf8()),
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// 解析 `YYYY-MM-DD` 形式的日期，返回 1970-01-01 起的天数，年份限定为 0 到 9999
+pub fn parse_date(text: &str) -> Result<i64> {
+    let invalid = || Error::InvalidConfig(format!("无效的日期 {}，格式应为 YYYY-MM-DD", text));
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next().and_then(|part| part.parse::<i64>().ok());
+    let mut next = || parts.next().and_then(|part| part.parse::<u32>().ok());
+    let (Some(year), Some(month), Some(day)) = (year, next(), next()) else {
+        return Err(invalid());
+    };
+    if !(0..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+    Ok(days)
+}
+
+/// 日期：按格式依次产出范围内的每一天，例如 `20231231`、`31-12-2023`
+///
+/// 先按格式、同一格式下按日期从早到晚排列。不同格式可能产出相同的候选，
+/// 例如日和月相同时 `DDMMYYYY` 与 `MMDDYYYY` 的结果一样，这里不去重
+pub struct Dates {
+    start: i64,
+    end: i64,
+    formats: Vec<String>,
+    tokens: Vec<Vec<Token>>,
+    position: u128,
+}
+
+impl Dates {
+    /// `start`、`end` 为 `YYYY-MM-DD` 形式，包含两端；`formats` 为空时使用 [`DEFAULT_DATE_FORMATS`]
+    pub fn new(start: &str, end: &str, formats: &[String]) -> Result<Self> {
+        let (start, end) = (parse_date(start)?, parse_date(end)?);
+        if start > end {
+            return Err(Error::InvalidConfig("起始日期晚于结束日期".to_string()));
+        }
+        let formats: Vec<String> = if formats.is_empty() {
+            DEFAULT_DATE_FORMATS.iter().map(|f| f.to_string()).collect()
+        } else {
+            formats.to_vec()
+        };
+        Ok(Self {
+            start,
+            end,
+            tokens: formats.iter().map(|f| parse_format(f)).collect(),
+            formats,
+            position: 0,
+        })
+    }
+
+    fn days(&self) -> u128 {
+        (self.end - self.start + 1) as u128
+    }
+}
+
+impl CandidateSource for Dates {
+    fn keyspace(&self) -> Option<u128> {
+        Some(self.days() * self.formats.len() as u128)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        let days = self.days();
+        let Some(tokens) = self.tokens.get((self.position / days) as usize) else {
+            return false;
+        };
+        let (year, month, day) = civil_from_days(self.start + (self.position % days) as i64);
+        let mut text = String::new();
+        for token in tokens {
+            match token {
+                Token::Year => text.push_str(&format!("{:04}", year)),
+                Token::ShortYear => text.push_str(&format!("{:02}", year.rem_euclid(100))),
+                Token::Month => text.push_str(&format!("{:02}", month)),
+                Token::ShortMonth => text.push_str(&month.to_string()),
+                Token::Day => text.push_str(&format!("{:02}", day)),
+                Token::ShortDay => text.push_str(&day.to_string()),
+                Token::Literal(c) => text.push(*c),
+            }
+        }
+        buf.clear();
+        buf.extend_from_slice(text.as_bytes());
+        self.position += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        self.position = position;
+        true
+    }
+
+    fn describe(&self) -> String {
+        let (y1, m1, d1) = civil_from_days(self.start);
+        let (y2, m2, d2) = civil_from_days(self.end);
+        format!(
+            "日期 {:04}-{:02}-{:02} 至 {:04}-{:02}-{:02}，{} 种格式",
+            y1,
+            m1,
+            d1,
+            y2,
+            m2,
+            d2,
+            self.formats.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+
+    #[test]
+    fn seek_matches_iteration() {
+        let formats = ["YYYYMMDD".to_string(), "D.M.YY".to_string()];
+        let all = check_seek(|| Dates::new("2023-12-30", "2024-01-02", &formats).unwrap());
+        let all: Vec<&[u8]> = all.iter().map(Vec::as_slice).collect();
+        assert_eq!(
+            all,
+            [
+                &b"20231230"[..],
+                b"20231231",
+                b"20240101",
+                b"20240102",
+                b"30.12.23",
+                b"31.12.23",
+                b"1.1.24",
+                b"2.1.24",
+            ]
+        );
+        let defaults = Dates::new("2024-01-01", "2024-12-31", &[]).unwrap();
+        assert_eq!(
+            defaults.keyspace(),
+            Some(366 * DEFAULT_DATE_FORMATS.len() as u128)
+        );
+    }
+
+    #[test]
+    fn parse_date_rejects_invalid_parts() {
+        assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+        assert!(parse_date("2024-02-29").is_ok());
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2023--1-01").is_err());
+        assert!(parse_date("2023-4294967295-01").is_err());
+        assert!(parse_date("10000-01-01").is_err());
+        assert!(parse_date("2023-01").is_err());
+        assert!(Dates::new("2024-01-02", "2024-01-01", &[]).is_err());
+    }
+}
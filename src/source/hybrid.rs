@@ -0,0 +1,98 @@
+use crate::source::{CandidateSource, Mask};
+
+/// 混合模式：字典（或其它来源）中的每个词与掩码的所有组合拼接
+pub struct Hybrid {
+    words: Box<dyn CandidateSource>,
+    mask: Mask,
+    /// 为 `true` 时掩码在前、词在后
+    mask_first: bool,
+    word: Vec<u8>,
+    mask_buf: Vec<u8>,
+    has_word: bool,
+}
+
+impl Hybrid {
+    pub fn new(words: impl CandidateSource + 'static, mask: Mask, mask_first: bool) -> Self {
+        Self {
+            words: Box::new(words),
+            mask,
+            mask_first,
+            word: Vec::new(),
+            mask_buf: Vec::new(),
+            has_word: false,
+        }
+    }
+}
+
+impl CandidateSource for Hybrid {
+    fn keyspace(&self) -> Option<u128> {
+        self.words.keyspace()?.checked_mul(self.mask.keyspace()?)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        loop {
+            if self.has_word && self.mask.next_candidate(&mut self.mask_buf) {
+                break;
+            }
+            if !self.words.next_candidate(&mut self.word) {
+                return false;
+            }
+            self.has_word = true;
+            self.mask.seek(0);
+        }
+
+        buf.clear();
+        if self.mask_first {
+            buf.extend_from_slice(&self.mask_buf);
+            buf.extend_from_slice(&self.word);
+        } else {
+            buf.extend_from_slice(&self.word);
+            buf.extend_from_slice(&self.mask_buf);
+        }
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        let Some(mask_keyspace) = self.mask.keyspace().filter(|&k| k > 0) else {
+            return false;
+        };
+        if !self.words.seek(position / mask_keyspace) {
+            return false;
+        }
+        let mask_position = position % mask_keyspace;
+        self.has_word = mask_position > 0 && self.words.next_candidate(&mut self.word);
+        self.mask.seek(mask_position);
+        true
+    }
+
+    fn describe(&self) -> String {
+        if self.mask_first {
+            format!("{} + {}", self.mask.describe(), self.words.describe())
+        } else {
+            format!("{} + {}", self.words.describe(), self.mask.describe())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Wordlist;
+    use crate::source::testing::check_seek;
+
+    #[test]
+    fn seek_matches_iteration() {
+        for mask_first in [false, true] {
+            let all = check_seek(|| {
+                Hybrid::new(
+                    Wordlist::from_words(["ab", "cd", "ef"]),
+                    Mask::parse("?d", &[]).unwrap(),
+                    mask_first,
+                )
+            });
+            assert_eq!(all.len(), 30);
+            let expected: &[u8] = if mask_first { b"0ab" } else { b"ab0" };
+            assert_eq!(all[0], expected);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 键盘布局，只包含数字行和三行字母区的小写字符
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+}
+
+impl KeyboardLayout {
+    /// 每一行的字符和相对第一行向右错开的距离（以 1/4 键宽为单位）
+    fn rows(self) -> [(&'static str, i32); 4] {
+        match self {
+            KeyboardLayout::Qwerty => [
+                ("1234567890-=", 0),
+                ("qwertyuiop[]", 2),
+                ("asdfghjkl;'", 3),
+                ("zxcvbnm,./", 5),
+            ],
+            KeyboardLayout::Azerty => [
+                ("1234567890", 0),
+                ("azertyuiop", 2),
+                ("qsdfghjklm", 3),
+                ("wxcvbn,;:!", 5),
+            ],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "QWERTY",
+            KeyboardLayout::Azerty => "AZERTY",
+        }
+    }
+}
+
+/// 右、左、右下、左下、右上、左上，相邻的两行按实际的错位判断斜向的键
+const DIRECTIONS: usize = 6;
+/// 还没有走过任何一步
+const NO_DIRECTION: usize = DIRECTIONS;
+/// 最长的行走，路线数按长度递归计算，太长会耗尽栈和内存
+pub const MAX_WALK_LENGTH: usize = 64;
+
+/// 反方向，用于找到当前这一段的起点
+fn opposite(direction: usize) -> usize {
+    [1, 0, 5, 4, 3, 2][direction]
+}
+
+/// 行走到某个键时的状态：键、当前方向、当前方向上连续走了几步、还要产出几个键、还能转几次弯
+type State = (usize, usize, usize, usize, usize);
+
+/// 键盘行走：沿着相邻的键依次按下，例如 `qwerty`、`asdfgh`、`1q2w3e4r`
+///
+/// 改变方向或者跳到上一段起点右边的键平行再走一段（例如 `1qaz2wsx`）都算一次转弯。
+/// 按长度从短到长、同一长度下按起点和方向的顺序产出，每个候选只出现一次。
+///
+/// 只产出行走本身，`qwerty123` 这类后面跟着其它字符的口令需要与掩码拼接，
+/// 见 [`AttackMode::KeyboardWalk`](crate::attack::AttackMode::KeyboardWalk) 的 `mask`
+pub struct KeyboardWalk {
+    layout: KeyboardLayout,
+    keys: Vec<u8>,
+    /// `neighbors[k][d]` 为键 k 在方向 d 上相邻的键
+    neighbors: Vec<[Option<usize>; DIRECTIONS]>,
+    /// 从某个状态出发还能走出的路线数，溢出时为 `None`
+    counts: HashMap<State, Option<u128>>,
+    min_length: usize,
+    max_length: usize,
+    max_turns: usize,
+    position: u128,
+}
+
+impl KeyboardWalk {
+    /// `max_length` 不能超过 [`MAX_WALK_LENGTH`]
+    pub fn new(
+        layout: KeyboardLayout,
+        min_length: usize,
+        max_length: usize,
+        max_turns: usize,
+    ) -> Result<Self> {
+        if max_length > MAX_WALK_LENGTH {
+            return Err(Error::InvalidConfig(format!(
+                "键盘行走最长 {} 个键",
+                MAX_WALK_LENGTH
+            )));
+        }
+        let mut keys = Vec::new();
+        let mut places = Vec::new();
+        for (row, (chars, offset)) in layout.rows().into_iter().enumerate() {
+            for (column, key) in chars.bytes().enumerate() {
+                keys.push(key);
+                places.push((row as i32, offset + column as i32 * 4));
+            }
+        }
+        let find = |row: i32, dx: std::ops::RangeInclusive<i32>, x: i32| {
+            places
+                .iter()
+                .position(|&(r, px)| r == row && dx.contains(&(px - x)))
+        };
+        let neighbors = places
+            .iter()
+            .map(|&(row, x)| {
+                [
+                    find(row, 4..=4, x),
+                    find(row, -4..=-4, x),
+                    find(row + 1, 0..=3, x),
+                    find(row + 1, -4..=-1, x),
+                    find(row - 1, 1..=4, x),
+                    find(row - 1, -3..=0, x),
+                ]
+            })
+            .collect();
+
+        let mut walk = Self {
+            layout,
+            keys,
+            neighbors,
+            counts: HashMap::new(),
+            min_length: min_length.max(1),
+            max_length,
+            max_turns,
+            position: 0,
+        };
+        for length in walk.min_length..=walk.max_length {
+            for key in 0..walk.keys.len() {
+                walk.fill((key, NO_DIRECTION, 0, length - 1, max_turns));
+            }
+        }
+        Ok(walk)
+    }
+
+    /// 从 `state` 出发走一步后可能到达的状态，按固定顺序排列
+    fn moves(&self, state: State) -> [Option<State>; DIRECTIONS + 1] {
+        let (key, direction, run, remaining, turns) = state;
+        let mut moves = [None; DIRECTIONS + 1];
+        if remaining == 0 {
+            return moves;
+        }
+        for (next_direction, slot) in moves.iter_mut().take(DIRECTIONS).enumerate() {
+            let Some(next) = self.neighbors[key][next_direction] else {
+                continue;
+            };
+            let straight = direction == NO_DIRECTION || direction == next_direction;
+            *slot = match (straight, turns) {
+                (true, _) => Some((next, next_direction, run + 1, remaining - 1, turns)),
+                (false, 0) => None,
+                (false, _) => Some((next, next_direction, 1, remaining - 1, turns - 1)),
+            };
+        }
+        // 平行再走一段：只在竖直或斜向连续走了至少两步后允许，这样跳到的键不会与相邻的键重复
+        if direction >= 2 && direction != NO_DIRECTION && run >= 2 && turns > 0 {
+            let mut start = key;
+            for _ in 0..run {
+                start = self.neighbors[start][opposite(direction)].unwrap_or(start);
+            }
+            if let Some(next) = self.neighbors[start][0] {
+                moves[DIRECTIONS] = Some((next, direction, 0, remaining - 1, turns - 1));
+            }
+        }
+        moves
+    }
+
+    /// 计算并缓存从 `state` 出发的路线数
+    fn fill(&mut self, state: State) -> Option<u128> {
+        if state.3 == 0 {
+            return Some(1);
+        }
+        if let Some(&count) = self.counts.get(&state) {
+            return count;
+        }
+        let mut count = Some(0u128);
+        for next in self.moves(state).into_iter().flatten() {
+            let next_count = self.fill(next);
+            count = count.and_then(|total| total.checked_add(next_count?));
+        }
+        self.counts.insert(state, count);
+        count
+    }
+
+    fn count(&self, state: State) -> Option<u128> {
+        if state.3 == 0 {
+            return Some(1);
+        }
+        self.counts.get(&state).copied().flatten()
+    }
+
+    /// 把第 `position` 个候选写入 `buf`，超出范围时返回 `false`
+    fn write(&self, mut position: u128, buf: &mut Vec<u8>) -> bool {
+        for length in self.min_length..=self.max_length {
+            for key in 0..self.keys.len() {
+                let state = (key, NO_DIRECTION, 0, length - 1, self.max_turns);
+                let Some(count) = self.count(state) else {
+                    return false;
+                };
+                if position < count {
+                    self.write_walk(state, position, buf);
+                    return true;
+                }
+                position -= count;
+            }
+        }
+        false
+    }
+
+    fn write_walk(&self, mut state: State, mut position: u128, buf: &mut Vec<u8>) {
+        buf.clear();
+        buf.push(self.keys[state.0]);
+        while state.3 > 0 {
+            for next in self.moves(state).into_iter().flatten() {
+                let count = self.count(next).unwrap_or(0);
+                if position < count {
+                    buf.push(self.keys[next.0]);
+                    state = next;
+                    break;
+                }
+                position -= count;
+            }
+        }
+    }
+}
+
+impl CandidateSource for KeyboardWalk {
+    fn keyspace(&self) -> Option<u128> {
+        let mut total = 0u128;
+        for length in self.min_length..=self.max_length {
+            for key in 0..self.keys.len() {
+                total = total.checked_add(self.count((
+                    key,
+                    NO_DIRECTION,
+                    0,
+                    length - 1,
+                    self.max_turns,
+                ))?)?;
+            }
+        }
+        Some(total)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.write(self.position, buf) {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        if self.keyspace().is_none() {
+            return false;
+        }
+        self.position = position;
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} 键盘行走 {}-{} 个键，最多转弯 {} 次",
+            self.layout.name(),
+            self.min_length,
+            self.max_length,
+            self.max_turns
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::{check_seek, collect};
+
+    #[test]
+    fn seek_matches_iteration() {
+        let all = check_seek(|| KeyboardWalk::new(KeyboardLayout::Qwerty, 2, 3, 1).unwrap());
+        let mut unique = all.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), all.len());
+        for walk in ["qw", "wq", "1qa", "asd", "qas"] {
+            assert!(all.contains(&walk.as_bytes().to_vec()), "{}", walk);
+        }
+    }
+
+    #[test]
+    fn turns_and_parallel_segments() {
+        let all = collect(&mut KeyboardWalk::new(KeyboardLayout::Qwerty, 4, 4, 1).unwrap());
+        assert!(all.contains(&b"rewq".to_vec()));
+        // 需要转弯两次
+        assert!(!all.contains(&b"qasx".to_vec()));
+        let all = collect(&mut KeyboardWalk::new(KeyboardLayout::Qwerty, 8, 8, 1).unwrap());
+        assert!(all.contains(&b"1qaz2wsx".to_vec()));
+        assert!(all.contains(&b"qwertyui".to_vec()));
+    }
+
+    #[test]
+    fn length_is_capped() {
+        assert!(KeyboardWalk::new(KeyboardLayout::Azerty, 1, MAX_WALK_LENGTH, 0).is_ok());
+        assert!(KeyboardWalk::new(KeyboardLayout::Azerty, 1, MAX_WALK_LENGTH + 1, 2).is_err());
+    }
+}
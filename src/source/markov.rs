@@ -0,0 +1,278 @@
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 从字典中统计的字符频率，类似 hashcat 的 `.hcstat`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkovStats {
+    /// 第 i 位上各字符出现的次数
+    pub positions: Vec<BTreeMap<char, u64>>,
+    /// 第 i 位上，前一个字符后面各字符出现的次数，第 0 位为空
+    pub transitions: Vec<BTreeMap<char, BTreeMap<char, u64>>>,
+}
+
+impl MarkovStats {
+    /// 最多统计的位数，更靠后的位置沿用最后一位的统计
+    pub const MAX_POSITIONS: usize = 32;
+
+    /// 统计来源中的全部候选，不是 UTF-8 的部分按替换字符处理
+    pub fn train(source: &mut impl CandidateSource) -> Self {
+        let mut stats = Self::default();
+        let mut buf = Vec::new();
+        while source.next_candidate(&mut buf) {
+            stats.add_word(&String::from_utf8_lossy(&buf));
+        }
+        stats
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        let mut prev = None;
+        for (position, c) in word.chars().take(Self::MAX_POSITIONS).enumerate() {
+            if self.positions.len() <= position {
+                self.positions.push(BTreeMap::new());
+                self.transitions.push(BTreeMap::new());
+            }
+            *self.positions[position].entry(c).or_default() += 1;
+            if let Some(prev) = prev {
+                *self.transitions[position]
+                    .entry(prev)
+                    .or_default()
+                    .entry(c)
+                    .or_default() += 1;
+            }
+            prev = Some(c);
+        }
+    }
+
+    /// 统计过的词数
+    pub fn words(&self) -> u64 {
+        self.positions
+            .first()
+            .map_or(0, |counts| counts.values().sum())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        serde_json::from_str(&text).map_err(|source| Error::Json {
+            segment: "Markov 统计文件",
+            source,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = serde_json::to_string(self).map_err(|source| Error::Json {
+            segment: "Markov 统计文件",
+            source,
+        })?;
+        fs::write(path, text).map_err(|e| Error::io(path, e))
+    }
+
+    /// 第 `position` 位在前一个字符为 `prev` 时，`charset` 的下标按出现次数从多到少排列
+    fn order(&self, position: usize, prev: Option<char>, charset: &[char]) -> Vec<usize> {
+        let count = |c: char| -> (u64, u64) {
+            let Some(counts) = self.positions.get(position) else {
+                return (0, 0);
+            };
+            let single = counts.get(&c).copied().unwrap_or(0);
+            let after = prev
+                .and_then(|prev| self.transitions[position].get(&prev))
+                .and_then(|next| next.get(&c))
+                .copied()
+                .unwrap_or(0);
+            (after, single)
+        };
+        let mut order: Vec<usize> = (0..charset.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(count(charset[i])));
+        order
+    }
+}
+
+/// 按 Markov 统计排序的暴力破解：每一位的字符按在前一个字符之后出现的频率排列
+///
+/// 不设阈值时每一位仍然包含字符集中的全部字符，候选与普通暴力破解完全相同，只是顺序不同；
+/// 阈值为 N 时每一位只取最可能的 N 个字符
+pub struct Markov {
+    charset: Vec<char>,
+    /// `orders[p][k]` 为第 p 位在前一个字符是 `charset[k]` 时的字符顺序，第 0 位只有一行
+    orders: Vec<Vec<Vec<usize>>>,
+    /// 每一位实际使用的字符数
+    width: usize,
+    min_length: usize,
+    max_length: usize,
+    current_length: usize,
+    /// 每一位在对应顺序中的排名
+    ranks: Vec<usize>,
+    started: bool,
+}
+
+impl Markov {
+    /// `threshold` 为 0 时不裁剪
+    pub fn new(
+        stats: &MarkovStats,
+        charset: &str,
+        min_length: usize,
+        max_length: usize,
+        threshold: usize,
+    ) -> Self {
+        let charset: Vec<char> = charset.chars().collect();
+        let rows = max_length.min(stats.positions.len().max(1));
+        let orders = (0..rows)
+            .map(|position| {
+                if position == 0 {
+                    vec![stats.order(0, None, &charset)]
+                } else {
+                    charset
+                        .iter()
+                        .map(|&prev| stats.order(position, Some(prev), &charset))
+                        .collect()
+                }
+            })
+            .collect();
+        let width = match threshold {
+            0 => charset.len(),
+            n => n.min(charset.len()),
+        };
+        Self {
+            charset,
+            orders,
+            width,
+            min_length,
+            max_length,
+            current_length: min_length,
+            ranks: vec![0; min_length],
+            started: false,
+        }
+    }
+
+    fn advance(&mut self) -> bool {
+        if self.width == 0 || self.current_length > self.max_length {
+            return false;
+        }
+        if !self.started {
+            self.started = true;
+            return true;
+        }
+        for i in (0..self.current_length).rev() {
+            self.ranks[i] += 1;
+            if self.ranks[i] < self.width {
+                return true;
+            }
+            self.ranks[i] = 0;
+        }
+
+        // 当前长度已经遍历完，进入下一个长度
+        self.current_length += 1;
+        self.ranks = vec![0; self.current_length];
+        self.current_length <= self.max_length
+    }
+}
+
+impl CandidateSource for Markov {
+    fn keyspace(&self) -> Option<u128> {
+        let base = self.width as u128;
+        (self.min_length..=self.max_length).try_fold(0u128, |total, length| {
+            total.checked_add(base.checked_pow(length as u32)?)
+        })
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.advance() {
+            return false;
+        }
+        buf.clear();
+        let mut utf8 = [0u8; 4];
+        let mut prev = 0;
+        for (position, &rank) in self.ranks.iter().enumerate() {
+            let rows = &self.orders[position.min(self.orders.len() - 1)];
+            let row = if rows.len() == 1 {
+                &rows[0]
+            } else {
+                &rows[prev]
+            };
+            prev = row[rank];
+            buf.extend_from_slice(self.charset[prev].encode_utf8(&mut utf8).as_bytes());
+        }
+        true
+    }
+
+    fn seek(&mut self, mut position: u128) -> bool {
+        let base = self.width as u128;
+        for length in self.min_length..=self.max_length {
+            let Some(count) = base.checked_pow(length as u32) else {
+                return false;
+            };
+            if position < count {
+                self.current_length = length;
+                self.ranks = vec![0; length];
+                for rank in self.ranks.iter_mut().rev() {
+                    *rank = (position % base) as usize;
+                    position /= base;
+                }
+                self.started = false;
+                return true;
+            }
+            position -= count;
+        }
+        self.current_length = self.max_length + 1;
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "Markov 暴力破解 {}-{} 位，字符集 {} 个字符，每位取前 {} 个",
+            self.min_length,
+            self.max_length,
+            self.charset.len(),
+            self.width
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Wordlist;
+    use crate::source::testing::check_seek;
+
+    fn stats() -> MarkovStats {
+        MarkovStats::train(&mut Wordlist::from_words(["ba", "bb", "ca", "ba"]))
+    }
+
+    #[test]
+    fn seek_matches_iteration() {
+        let stats = stats();
+        let mut all = check_seek(|| Markov::new(&stats, "abc", 1, 2, 0));
+        assert_eq!(all.len(), 3 + 9);
+        // 第一位 b 最常见，b 后面 a 最常见
+        assert_eq!(all[0], b"b");
+        assert_eq!(all[3], b"ba");
+        // 不设阈值时与普通暴力破解的候选相同
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 12);
+    }
+
+    #[test]
+    fn threshold_keeps_most_likely() {
+        let stats = stats();
+        let all = check_seek(|| Markov::new(&stats, "abc", 2, 2, 1));
+        assert_eq!(all, [b"ba"]);
+    }
+
+    #[test]
+    fn stats_round_trip() {
+        let stats = stats();
+        assert_eq!(stats.words(), 4);
+        let path =
+            std::env::temp_dir().join(format!("jwt_cracker_{}_markov.json", std::process::id()));
+        stats.save(&path).unwrap();
+        let loaded = MarkovStats::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded, stats);
+    }
+}
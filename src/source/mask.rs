@@ -0,0 +1,185 @@
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use crate::utils::combination_generator::{
+    DIGITS, LOWERCASE, SPECIAL, UPPERCASE, normalize_charset,
+};
+
+/// hashcat 风格的掩码，例如 `?u?l?l?l?d?d`
+///
+/// 内置字符集: `?l` 小写、`?u` 大写、`?d` 数字、`?s` 特殊字符（含空格）、`?a` 全部、
+/// `?h`/`?H` 十六进制，`?b` 为 0x00-0xff 的全部字节，`?1`-`?4` 为自定义字符集，`??` 表示问号本身
+pub struct Mask {
+    pattern: String,
+    /// 每一位可选的符号，按编码后的字节存放
+    positions: Vec<Vec<Vec<u8>>>,
+    indices: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+impl Mask {
+    pub fn parse(pattern: &str, custom_charsets: &[String]) -> Result<Self> {
+        let mut positions = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '?' {
+                positions.push(vec![c.to_string().into_bytes()]);
+                continue;
+            }
+            let Some(class) = chars.next() else {
+                return Err(Error::InvalidConfig(format!(
+                    "掩码 {} 以单独的 ? 结尾",
+                    pattern
+                )));
+            };
+            let charset = match class {
+                '?' => "?".to_string(),
+                'b' => {
+                    positions.push((0..=u8::MAX).map(|byte| vec![byte]).collect());
+                    continue;
+                }
+                _ => builtin_charset(class, custom_charsets).ok_or_else(|| {
+                    Error::InvalidConfig(format!("掩码 {} 中有未知的字符集 ?{}", pattern, class))
+                })?,
+            };
+            positions.push(
+                normalize_charset(&charset)
+                    .chars()
+                    .map(|c| c.to_string().into_bytes())
+                    .collect(),
+            );
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            indices: vec![0; positions.len()],
+            positions,
+            started: false,
+            done: false,
+        })
+    }
+
+    /// 掩码的位数
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+/// `?x` 对应的字符集，自定义字符集中也可以引用内置字符集
+fn builtin_charset(class: char, custom_charsets: &[String]) -> Option<String> {
+    let special = format!(" {}", SPECIAL);
+    Some(match class {
+        'l' => LOWERCASE.to_string(),
+        'u' => UPPERCASE.to_string(),
+        'd' => DIGITS.to_string(),
+        's' => special,
+        'a' => format!("{}{}{}{}", LOWERCASE, UPPERCASE, DIGITS, special),
+        'h' => "0123456789abcdef".to_string(),
+        'H' => "0123456789ABCDEF".to_string(),
+        '1'..='4' => expand_custom(custom_charsets.get(class as usize - '1' as usize)?),
+        _ => return None,
+    })
+}
+
+/// 展开自定义字符集里引用的内置字符集，例如 `?l?d_`
+fn expand_custom(custom: &str) -> String {
+    let mut expanded = String::new();
+    let mut chars = custom.chars();
+    while let Some(c) = chars.next() {
+        if c != '?' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('?') | None => expanded.push('?'),
+            Some(class) => expanded.push_str(&builtin_charset(class, &[]).unwrap_or_default()),
+        }
+    }
+    expanded
+}
+
+impl CandidateSource for Mask {
+    fn keyspace(&self) -> Option<u128> {
+        self.positions.iter().try_fold(1u128, |total, charset| {
+            total.checked_mul(charset.len() as u128)
+        })
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if self.done || self.positions.iter().any(Vec::is_empty) {
+            return false;
+        }
+        if self.started {
+            let mut carried = true;
+            for i in (0..self.indices.len()).rev() {
+                self.indices[i] += 1;
+                if self.indices[i] < self.positions[i].len() {
+                    carried = false;
+                    break;
+                }
+                self.indices[i] = 0;
+            }
+            if carried {
+                self.done = true;
+                return false;
+            }
+        }
+        self.started = true;
+
+        buf.clear();
+        for (charset, &i) in self.positions.iter().zip(&self.indices) {
+            buf.extend_from_slice(&charset[i]);
+        }
+        true
+    }
+
+    fn seek(&mut self, mut position: u128) -> bool {
+        let Some(keyspace) = self.keyspace() else {
+            return false;
+        };
+        self.started = false;
+        self.done = position >= keyspace;
+        for (index, charset) in self.indices.iter_mut().zip(&self.positions).rev() {
+            let base = charset.len().max(1) as u128;
+            *index = (position % base) as usize;
+            position /= base;
+        }
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!("掩码 {}", self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+
+    #[test]
+    fn seek_matches_iteration() {
+        let all = check_seek(|| Mask::parse("a?1?d", &["xy".to_string()]).unwrap());
+        assert_eq!(all.len(), 20);
+        assert_eq!(all[0], b"ax0");
+        assert_eq!(all[19], b"ay9");
+    }
+
+    #[test]
+    fn byte_class_covers_every_byte() {
+        let all = check_seek(|| Mask::parse("?b", &[]).unwrap());
+        assert_eq!(all, (0..=u8::MAX).map(|b| vec![b]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn escaped_question_mark_and_errors() {
+        let all = check_seek(|| Mask::parse("??", &[]).unwrap());
+        assert_eq!(all, [b"?"]);
+        assert!(Mask::parse("ab?", &[]).is_err());
+        assert!(Mask::parse("?z", &[]).is_err());
+        assert!(Mask::parse("?1", &[]).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! 候选密钥来源：所有攻击模式都实现 [`CandidateSource`]，爆破引擎只依赖这个 trait
+
+mod chain;
+mod dates;
+mod hybrid;
+mod keyboard;
+mod markov;
+mod mask;
+mod prince;
+mod rules;
+mod stdin;
+mod wordlist;
+
+pub use chain::{Affix, Chain, Slice};
+pub use dates::{DEFAULT_DATE_FORMATS, Dates, parse_date};
+pub use hybrid::Hybrid;
+pub use keyboard::{KeyboardLayout, KeyboardWalk};
+pub use markov::{Markov, MarkovStats};
+pub use mask::Mask;
+pub use prince::Prince;
+pub use rules::{Rule, Rules};
+pub use stdin::Stdin;
+pub use wordlist::Wordlist;
+
+/// 按顺序产出候选密钥的来源
+pub trait CandidateSource: Send {
+    /// 候选密钥总数，无法预知时返回 `None`
+    fn keyspace(&self) -> Option<u128>;
+
+    /// 把下一个候选密钥写入 `buf`（会先清空），没有更多候选时返回 `false`
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool;
+
+    /// 跳到第 `position` 个候选（从 0 开始），不支持跳转时返回 `false`
+    fn seek(&mut self, position: u128) -> bool {
+        let _ = position;
+        false
+    }
+
+    /// 用于界面显示的简短描述
+    fn describe(&self) -> String;
+}
+
+impl<S: CandidateSource + ?Sized> CandidateSource for Box<S> {
+    fn keyspace(&self) -> Option<u128> {
+        (**self).keyspace()
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        (**self).next_candidate(buf)
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        (**self).seek(position)
+    }
+
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+}
+
+/// 把 `keyspace` 个候选平均切分为最多 `parts` 段，返回每段的 `(起点, 数量)`
+pub fn split_keyspace(keyspace: u128, parts: usize) -> Vec<(u128, u128)> {
+    let parts = (parts.max(1) as u128).min(keyspace.max(1));
+    let chunk = keyspace / parts;
+    let remainder = keyspace % parts;
+    let mut start = 0;
+    (0..parts)
+        .map(|i| {
+            let len = chunk + u128::from(i < remainder);
+            let range = (start, len);
+            start += len;
+            range
+        })
+        .collect()
+}
+
+/// 各个来源共用的测试辅助函数
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::CandidateSource;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// 读出来源中剩下的全部候选
+    pub(crate) fn collect(source: &mut impl CandidateSource) -> Vec<Vec<u8>> {
+        let mut candidates = Vec::new();
+        let mut buf = Vec::new();
+        while source.next_candidate(&mut buf) {
+            candidates.push(buf.clone());
+        }
+        candidates
+    }
+
+    /// 检查 `keyspace()` 等于实际产出的数量，`seek(n)` 之后得到第 n 个候选，返回全部候选
+    ///
+    /// `make` 每次返回一个新的来源；除了新来源上的跳转，也会在同一个来源上从后往前跳，
+    /// 最后回到开头再完整读一遍
+    pub(crate) fn check_seek<S: CandidateSource>(make: impl Fn() -> S) -> Vec<Vec<u8>> {
+        let all = collect(&mut make());
+        assert_eq!(make().keyspace(), Some(all.len() as u128));
+        let mut buf = Vec::new();
+        for (n, expected) in all.iter().enumerate() {
+            let mut source = make();
+            assert!(source.seek(n as u128));
+            assert!(source.next_candidate(&mut buf), "seek({}) 后没有候选", n);
+            assert_eq!(&buf, expected, "seek({})", n);
+        }
+        let mut source = make();
+        assert!(source.seek(all.len() as u128));
+        assert!(!source.next_candidate(&mut buf));
+        for (n, expected) in all.iter().enumerate().rev() {
+            assert!(source.seek(n as u128));
+            assert!(source.next_candidate(&mut buf));
+            assert_eq!(&buf, expected, "往回 seek({})", n);
+        }
+        assert!(source.seek(0));
+        assert_eq!(collect(&mut source), all, "回到开头后重新读一遍");
+        all
+    }
+
+    /// 在临时目录中写一个字典文件，`name` 在所有测试中应唯一
+    pub(crate) fn temp_wordlist(name: &str, lines: &[&str]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("jwt_cracker_{}_{}.txt", std::process::id(), name));
+        fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+}
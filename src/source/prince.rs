@@ -0,0 +1,180 @@
+use crate::source::{CandidateSource, Wordlist};
+
+/// PRINCE：把字典中的词拼接成链，例如 `blue` + `horse` + `staple`
+///
+/// 按总长度（字节）从短到长、同一长度下按元素个数从少到多的顺序产出，
+/// 同一长度和个数下先按第一个词的长度、再按词在字典中的顺序排列
+pub struct Prince {
+    description: String,
+    /// `words[l]` 为长度为 l 的全部词（已去重）
+    words: Vec<Vec<Vec<u8>>>,
+    /// `counts[l][k]` 为总长度 l、由 k 个词组成的链的数量，溢出时为 `None`
+    counts: Vec<Vec<Option<u128>>>,
+    min_length: usize,
+    max_length: usize,
+    min_elements: usize,
+    max_elements: usize,
+    position: u128,
+}
+
+impl Prince {
+    /// 会把字典完整读入内存，长于 `max_length` 的词和空行被忽略
+    pub fn new(
+        mut wordlist: Wordlist,
+        min_length: usize,
+        max_length: usize,
+        min_elements: usize,
+        max_elements: usize,
+    ) -> Self {
+        let min_elements = min_elements.max(1);
+        let mut words = vec![Vec::new(); max_length + 1];
+        let mut buf = Vec::new();
+        while wordlist.next_candidate(&mut buf) {
+            if (1..=max_length).contains(&buf.len()) {
+                words[buf.len()].push(buf.clone());
+            }
+        }
+        for group in &mut words {
+            group.sort();
+            group.dedup();
+        }
+
+        let mut counts = vec![vec![Some(0u128); max_elements + 1]; max_length + 1];
+        counts[0][0] = Some(1);
+        for length in 1..=max_length {
+            for elements in 1..=max_elements {
+                counts[length][elements] = (1..=length).try_fold(0u128, |total, first| {
+                    let chains = (words[first].len() as u128)
+                        .checked_mul(counts[length - first][elements - 1]?)?;
+                    total.checked_add(chains)
+                });
+            }
+        }
+
+        Self {
+            description: wordlist.describe(),
+            words,
+            counts,
+            min_length,
+            max_length,
+            min_elements,
+            max_elements,
+            position: 0,
+        }
+    }
+
+    /// 字典中可用的词数
+    pub fn word_count(&self) -> usize {
+        self.words.iter().map(Vec::len).sum()
+    }
+
+    /// 把第 `position` 个候选写入 `buf`，超出范围时返回 `false`
+    fn write(&self, mut position: u128, buf: &mut Vec<u8>) -> bool {
+        for length in self.min_length..=self.max_length {
+            for elements in self.min_elements..=self.max_elements {
+                let Some(count) = self.counts[length][elements] else {
+                    return false;
+                };
+                if position < count {
+                    self.write_chain(length, elements, position, buf);
+                    return true;
+                }
+                position -= count;
+            }
+        }
+        false
+    }
+
+    fn write_chain(
+        &self,
+        mut length: usize,
+        elements: usize,
+        mut position: u128,
+        buf: &mut Vec<u8>,
+    ) {
+        buf.clear();
+        for remaining in (0..elements).rev() {
+            for first in 1..=length {
+                let rest = self.counts[length - first][remaining].unwrap_or(0);
+                let group = self.words[first].len() as u128 * rest;
+                if position < group {
+                    buf.extend_from_slice(&self.words[first][(position / rest) as usize]);
+                    position %= rest;
+                    length -= first;
+                    break;
+                }
+                position -= group;
+            }
+        }
+    }
+}
+
+impl CandidateSource for Prince {
+    fn keyspace(&self) -> Option<u128> {
+        let mut total = 0u128;
+        for length in self.min_length..=self.max_length {
+            for elements in self.min_elements..=self.max_elements {
+                total = total.checked_add(self.counts[length][elements]?)?;
+            }
+        }
+        Some(total)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.write(self.position, buf) {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        if self.keyspace().is_none() {
+            return false;
+        }
+        self.position = position;
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "PRINCE {}，{} 个词，{}-{} 个元素，长度 {}-{}",
+            self.description,
+            self.word_count(),
+            self.min_elements,
+            self.max_elements,
+            self.min_length,
+            self.max_length
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+
+    #[test]
+    fn seek_matches_iteration() {
+        let make = || {
+            Prince::new(
+                Wordlist::from_words(["a", "bc", "a", "", "long"]),
+                1,
+                3,
+                1,
+                2,
+            )
+        };
+        assert_eq!(make().word_count(), 2);
+        let all = check_seek(make);
+        let all: Vec<&[u8]> = all.iter().map(Vec::as_slice).collect();
+        assert_eq!(all, [&b"a"[..], b"bc", b"aa", b"abc", b"bca"]);
+    }
+
+    #[test]
+    fn element_range() {
+        let all = check_seek(|| Prince::new(Wordlist::from_words(["x", "y"]), 1, 4, 3, 3));
+        assert_eq!(all.len(), 8);
+        assert!(all.iter().all(|chain| chain.len() == 3));
+    }
+}
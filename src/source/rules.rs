@@ -0,0 +1,278 @@
+use crate::error::{Error, Result};
+use crate::source::{CandidateSource, Wordlist};
+
+/// 单个规则函数，语义与 hashcat 相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleOp {
+    Noop,
+    Lower,
+    Upper,
+    Capitalize,
+    InvertCapitalize,
+    ToggleAll,
+    ToggleAt(usize),
+    Reverse,
+    Duplicate,
+    Reflect,
+    Append(u8),
+    Prepend(u8),
+    DeleteFirst,
+    DeleteLast,
+    DeleteAt(usize),
+    Replace(u8, u8),
+    Purge(u8),
+}
+
+/// 一条变形规则，例如 `c $1 $2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    text: String,
+    ops: Vec<RuleOp>,
+}
+
+impl Rule {
+    /// 支持 `: l u c C t TN r d f $X ^X [ ] DN sXY @X`
+    pub fn parse(text: &str) -> Result<Self> {
+        let bytes = text.as_bytes();
+        let mut ops = Vec::new();
+        let mut i = 0;
+        let arg = |i: usize| {
+            bytes
+                .get(i)
+                .copied()
+                .ok_or_else(|| Error::InvalidConfig(format!("规则 {} 缺少参数", text)))
+        };
+        let position = |i: usize| {
+            let c = arg(i)?;
+            match c {
+                b'0'..=b'9' => Ok((c - b'0') as usize),
+                b'A'..=b'Z' => Ok((c - b'A') as usize + 10),
+                _ => Err(Error::InvalidConfig(format!(
+                    "规则 {} 中的位置 {} 无效",
+                    text, c as char
+                ))),
+            }
+        };
+        while i < bytes.len() {
+            let op = match bytes[i] {
+                b' ' | b'\t' => {
+                    i += 1;
+                    continue;
+                }
+                b':' => RuleOp::Noop,
+                b'l' => RuleOp::Lower,
+                b'u' => RuleOp::Upper,
+                b'c' => RuleOp::Capitalize,
+                b'C' => RuleOp::InvertCapitalize,
+                b't' => RuleOp::ToggleAll,
+                b'r' => RuleOp::Reverse,
+                b'd' => RuleOp::Duplicate,
+                b'f' => RuleOp::Reflect,
+                b'[' => RuleOp::DeleteFirst,
+                b']' => RuleOp::DeleteLast,
+                b'T' => {
+                    i += 1;
+                    RuleOp::ToggleAt(position(i)?)
+                }
+                b'D' => {
+                    i += 1;
+                    RuleOp::DeleteAt(position(i)?)
+                }
+                b'$' => {
+                    i += 1;
+                    RuleOp::Append(arg(i)?)
+                }
+                b'^' => {
+                    i += 1;
+                    RuleOp::Prepend(arg(i)?)
+                }
+                b'@' => {
+                    i += 1;
+                    RuleOp::Purge(arg(i)?)
+                }
+                b's' => {
+                    i += 2;
+                    RuleOp::Replace(arg(i - 1)?, arg(i)?)
+                }
+                other => {
+                    return Err(Error::InvalidConfig(format!(
+                        "规则 {} 中有不支持的函数 {}",
+                        text, other as char
+                    )));
+                }
+            };
+            ops.push(op);
+            i += 1;
+        }
+        Ok(Self {
+            text: text.to_string(),
+            ops,
+        })
+    }
+
+    /// 解析多行规则，忽略空行和以 `#` 开头的注释
+    pub fn parse_lines(text: &str) -> Result<Vec<Self>> {
+        text.lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Self::parse)
+            .collect()
+    }
+
+    /// 对 `word` 应用规则，结果写入 `out`
+    pub fn apply(&self, word: &[u8], out: &mut Vec<u8>) {
+        out.clear();
+        out.extend_from_slice(word);
+        for op in &self.ops {
+            match *op {
+                RuleOp::Noop => {}
+                RuleOp::Lower => out.make_ascii_lowercase(),
+                RuleOp::Upper => out.make_ascii_uppercase(),
+                RuleOp::Capitalize | RuleOp::InvertCapitalize => {
+                    let capitalize = *op == RuleOp::Capitalize;
+                    for (i, b) in out.iter_mut().enumerate() {
+                        *b = if (i == 0) == capitalize {
+                            b.to_ascii_uppercase()
+                        } else {
+                            b.to_ascii_lowercase()
+                        };
+                    }
+                }
+                RuleOp::ToggleAll => out.iter_mut().for_each(toggle_case),
+                RuleOp::ToggleAt(n) => {
+                    if let Some(b) = out.get_mut(n) {
+                        toggle_case(b);
+                    }
+                }
+                RuleOp::Reverse => out.reverse(),
+                RuleOp::Duplicate => out.extend_from_within(..),
+                RuleOp::Reflect => {
+                    let reversed: Vec<u8> = out.iter().rev().copied().collect();
+                    out.extend_from_slice(&reversed);
+                }
+                RuleOp::Append(c) => out.push(c),
+                RuleOp::Prepend(c) => out.insert(0, c),
+                RuleOp::DeleteFirst => {
+                    if !out.is_empty() {
+                        out.remove(0);
+                    }
+                }
+                RuleOp::DeleteLast => {
+                    out.pop();
+                }
+                RuleOp::DeleteAt(n) => {
+                    if n < out.len() {
+                        out.remove(n);
+                    }
+                }
+                RuleOp::Replace(from, to) => {
+                    out.iter_mut().filter(|b| **b == from).for_each(|b| *b = to)
+                }
+                RuleOp::Purge(c) => out.retain(|&b| b != c),
+            }
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+fn toggle_case(b: &mut u8) {
+    if b.is_ascii_lowercase() {
+        b.make_ascii_uppercase();
+    } else if b.is_ascii_uppercase() {
+        b.make_ascii_lowercase();
+    }
+}
+
+/// 字典加规则：每个词依次套用所有规则
+pub struct Rules {
+    words: Wordlist,
+    rules: Vec<Rule>,
+    word: Vec<u8>,
+    rule_index: usize,
+}
+
+impl Rules {
+    pub fn new(words: Wordlist, rules: Vec<Rule>) -> Self {
+        let rule_index = rules.len();
+        Self {
+            words,
+            rules,
+            word: Vec::new(),
+            rule_index,
+        }
+    }
+}
+
+impl CandidateSource for Rules {
+    fn keyspace(&self) -> Option<u128> {
+        self.words.keyspace()?.checked_mul(self.rules.len() as u128)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        if self.rule_index >= self.rules.len() {
+            if !self.words.next_candidate(&mut self.word) {
+                return false;
+            }
+            self.rule_index = 0;
+        }
+        self.rules[self.rule_index].apply(&self.word, buf);
+        self.rule_index += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        let count = self.rules.len().max(1) as u128;
+        if !self.words.seek(position / count) {
+            return false;
+        }
+        // 落在某个词的中间时先读出这个词，否则下一次调用时再读
+        let rule_index = (position % count) as usize;
+        self.rule_index = self.rules.len();
+        if rule_index > 0 && self.words.next_candidate(&mut self.word) {
+            self.rule_index = rule_index;
+        }
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!("{} × {} 条规则", self.words.describe(), self.rules.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+
+    fn apply(rule: &str, word: &str) -> String {
+        let mut out = Vec::new();
+        Rule::parse(rule).unwrap().apply(word.as_bytes(), &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn rule_functions() {
+        assert_eq!(apply(":", "Pass"), "Pass");
+        assert_eq!(apply("c $1 $2", "password"), "Password12");
+        assert_eq!(apply("u", "pass"), "PASS");
+        assert_eq!(apply("r", "abc"), "cba");
+        assert_eq!(apply("d", "ab"), "abab");
+        assert_eq!(apply("sa@", "banana"), "b@n@n@");
+        assert_eq!(apply("^x", "abc"), "xabc");
+        assert!(Rule::parse("Q").is_err());
+    }
+
+    #[test]
+    fn seek_matches_iteration() {
+        let rules = Rule::parse_lines(":\nu\n$!").unwrap();
+        let all = check_seek(|| Rules::new(Wordlist::from_words(["ab", "cd"]), rules.clone()));
+        let all: Vec<&[u8]> = all.iter().map(Vec::as_slice).collect();
+        assert_eq!(all, [&b"ab"[..], b"AB", b"ab!", b"cd", b"CD", b"cd!"]);
+    }
+}
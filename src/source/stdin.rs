@@ -0,0 +1,51 @@
+use crate::source::CandidateSource;
+use crate::source::wordlist::read_line;
+use std::io::{self, BufReader};
+
+/// 从标准输入逐行读取候选，便于和其他生成工具配合使用
+pub struct Stdin {
+    reader: BufReader<io::Stdin>,
+    consumed: u128,
+}
+
+impl Stdin {
+    pub fn new() -> Self {
+        Self {
+            reader: BufReader::new(io::stdin()),
+            consumed: 0,
+        }
+    }
+}
+
+impl Default for Stdin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CandidateSource for Stdin {
+    fn keyspace(&self) -> Option<u128> {
+        None
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        let read = read_line(&mut self.reader, buf).unwrap_or(false);
+        self.consumed += u128::from(read);
+        read
+    }
+
+    /// 只能向前跳过，用于从保存的进度继续
+    fn seek(&mut self, position: u128) -> bool {
+        let mut line = Vec::new();
+        while self.consumed < position {
+            if !self.next_candidate(&mut line) {
+                break;
+            }
+        }
+        self.consumed == position
+    }
+
+    fn describe(&self) -> String {
+        "标准输入".to_string()
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// 文件字典每隔这么多行记录一次字节偏移，`seek` 时先跳到最近的记录再逐行跳过
+const INDEX_STRIDE: u128 = 4096;
+
+enum Backing {
+    File {
+        path: PathBuf,
+        reader: BufReader<File>,
+        /// 第 `i * INDEX_STRIDE` 行开头的字节偏移
+        checkpoints: Vec<u64>,
+    },
+    Memory {
+        words: Vec<Vec<u8>>,
+        position: usize,
+    },
+}
+
+/// 字典来源：每行一个候选，行尾的 `\r\n` 会被去掉
+pub struct Wordlist {
+    backing: Backing,
+    lines: u128,
+}
+
+impl Wordlist {
+    /// 打开字典文件，会先完整读一遍以统计行数并建立稀疏的行偏移索引
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let index_lines = || -> io::Result<(u128, Vec<u64>)> {
+            let mut reader = BufReader::new(File::open(&path)?);
+            let mut lines = 0u128;
+            let mut offset = 0u64;
+            let mut checkpoints = Vec::new();
+            let mut line = Vec::new();
+            loop {
+                if lines.is_multiple_of(INDEX_STRIDE) {
+                    checkpoints.push(offset);
+                }
+                line.clear();
+                let read = reader.read_until(b'\n', &mut line)?;
+                if read == 0 {
+                    break;
+                }
+                offset += read as u64;
+                lines += 1;
+            }
+            Ok((lines, checkpoints))
+        };
+        let (lines, checkpoints) = index_lines().map_err(|e| Error::io(&path, e))?;
+        let reader = BufReader::new(File::open(&path).map_err(|e| Error::io(&path, e))?);
+        Ok(Self {
+            backing: Backing::File {
+                reader,
+                path,
+                checkpoints,
+            },
+            lines,
+        })
+    }
+
+    /// 内存中的词表，例如内置的常见密钥
+    pub fn from_words<I, W>(words: I) -> Self
+    where
+        I: IntoIterator<Item = W>,
+        W: AsRef<[u8]>,
+    {
+        let words: Vec<Vec<u8>> = words.into_iter().map(|w| w.as_ref().to_vec()).collect();
+        Self {
+            lines: words.len() as u128,
+            backing: Backing::Memory { words, position: 0 },
+        }
+    }
+
+    /// 回到第一个词
+    pub fn rewind(&mut self) -> io::Result<()> {
+        match &mut self.backing {
+            Backing::File { path, reader, .. } => *reader = BufReader::new(File::open(path)?),
+            Backing::Memory { position, .. } => *position = 0,
+        }
+        Ok(())
+    }
+}
+
+impl CandidateSource for Wordlist {
+    fn keyspace(&self) -> Option<u128> {
+        Some(self.lines)
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        match &mut self.backing {
+            Backing::File { reader, .. } => read_line(reader, buf).unwrap_or(false),
+            Backing::Memory { words, position } => match words.get(*position) {
+                Some(word) => {
+                    buf.clear();
+                    buf.extend_from_slice(word);
+                    *position += 1;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        match &mut self.backing {
+            Backing::File {
+                path,
+                reader,
+                checkpoints,
+            } => {
+                let position = position.min(self.lines);
+                let checkpoint = ((position / INDEX_STRIDE) as usize).min(checkpoints.len() - 1);
+                let jump = || -> io::Result<BufReader<File>> {
+                    let mut file = File::open(&*path)?;
+                    file.seek(SeekFrom::Start(checkpoints[checkpoint]))?;
+                    Ok(BufReader::new(file))
+                };
+                let Ok(jumped) = jump() else {
+                    return false;
+                };
+                *reader = jumped;
+                let mut line = Vec::new();
+                for _ in checkpoint as u128 * INDEX_STRIDE..position {
+                    if !read_line(reader, &mut line).unwrap_or(false) {
+                        break;
+                    }
+                }
+            }
+            Backing::Memory { words, position: p } => {
+                *p = position.min(words.len() as u128) as usize;
+            }
+        }
+        true
+    }
+
+    fn describe(&self) -> String {
+        match &self.backing {
+            Backing::File { path, .. } => format!("字典 {} ({} 行)", path.display(), self.lines),
+            Backing::Memory { words, .. } => format!("内置词表 ({} 个)", words.len()),
+        }
+    }
+}
+
+/// 读取一行到 `buf` 并去掉换行符，到达文件末尾时返回 `false`
+pub(crate) fn read_line(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> io::Result<bool> {
+    buf.clear();
+    if reader.read_until(b'\n', buf)? == 0 {
+        return Ok(false);
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::{check_seek, collect, temp_wordlist};
+
+    #[test]
+    fn file_strips_line_endings() {
+        let path = temp_wordlist("wordlist_endings", &["alpha\r", "", "gamma"]);
+        let all = check_seek(|| Wordlist::open(&path).unwrap());
+        assert_eq!(all, [b"alpha".to_vec(), Vec::new(), b"gamma".to_vec()]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn memory_seek_matches_iteration() {
+        let all = check_seek(|| Wordlist::from_words(["a", "b", "c"]));
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn seek_across_checkpoints() {
+        let words: Vec<String> = (0..INDEX_STRIDE * 2 + 10).map(|i| i.to_string()).collect();
+        let lines: Vec<&str> = words.iter().map(String::as_str).collect();
+        let path = temp_wordlist("wordlist_checkpoints", &lines);
+        let mut wordlist = Wordlist::open(&path).unwrap();
+        assert_eq!(wordlist.keyspace(), Some(words.len() as u128));
+        let mut buf = Vec::new();
+        for position in [
+            0,
+            1,
+            INDEX_STRIDE - 1,
+            INDEX_STRIDE,
+            INDEX_STRIDE * 2 + 9,
+            7,
+        ] {
+            assert!(wordlist.seek(position));
+            assert!(wordlist.next_candidate(&mut buf));
+            assert_eq!(buf, position.to_string().into_bytes());
+        }
+        assert!(wordlist.seek(words.len() as u128));
+        assert!(collect(&mut wordlist).is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+}
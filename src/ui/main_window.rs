@@ -1,19 +1,30 @@
+use crate::ui::audit_window::AuditWindow;
+use crate::ui::forge_window::ForgeWindow;
+use crate::ui::oracle_window::OracleWindow;
 use crate::ui::widget::spinner::Spinner;
-use crate::utils::combination_generator::CombinationGenerator;
-use crate::utils::jwt::verify_jwt_hs256_token;
-use base64::Engine;
-use base64::engine::general_purpose::URL_SAFE_NO_PAD;
-use crossbeam_channel::{Receiver, Sender, TryRecvError, unbounded};
+use crossbeam_channel::{Receiver, TryRecvError, unbounded};
 use eframe::egui;
 use egui::{
-    Align, CentralPanel, Checkbox, DragValue, Label, Layout, RichText, TextEdit, TopBottomPanel,
+    Align, CentralPanel, Checkbox, ComboBox, DragValue, Label, Layout, RichText, Slider, TextEdit,
+    TopBottomPanel,
 };
 use egui_extras::syntax_highlighting::{CodeTheme, highlight};
 use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
-use rayon::iter::{ParallelBridge, ParallelIterator};
+use jwt_cracker::job::JobOutput;
+use jwt_cracker::source::KeyboardLayout;
+use jwt_cracker::utils::claims::{
+    ClaimEdit, ESCALATION_TEMPLATES, apply_claim_edit, describe_time_claims,
+};
+use jwt_cracker::utils::jwt::{KeyEncoding, decode_segment, split_token, time_claim_warnings};
+use jwt_cracker::utils::time::SECS_PER_YEAR;
+use jwt_cracker::{
+    AttackConfig, AttackMode, CancelToken, Charset, Cracker, Event, Found, Job, LengthUnit,
+    RunOptions, Stats,
+};
 use serde_json::{Value, from_str};
-use std::sync::Arc;
+use std::path::PathBuf;
 use std::thread;
+use std::time::Duration;
 
 /// 主应用窗口结构体，包含所有状态
 pub(crate) struct MainWindow {
@@ -26,21 +37,72 @@ pub(crate) struct MainWindow {
     use_digits: bool,
     use_special: bool,
     use_user_charset: bool,
+    /// 暴力破解的长度按字节计算
+    length_in_bytes: bool,
+    /// 自定义字符集按字节范围解析，例如 `\x00-\xff`
+    user_charset_bytes: bool,
+    attack_kind: AttackKind,
+    try_common_first: bool,
+    threads: usize,
+    low_priority: bool,
+    pin_cores: bool,
+    /// 运行时间上限（秒），0 表示不限制
+    max_secs: u64,
+    /// 候选数量上限，0 表示不限制
+    max_candidates: u64,
+    /// 任务文件路径，和 `JwtCrackerCLI run` 使用同样的格式
+    job_path: String,
+    /// 加载或保存任务文件的结果
+    job_message: Option<RichText>,
+    mask: String,
+    mask_first: bool,
+    wordlist_path: String,
+    /// `JwtCrackerCLI train` 生成的 Markov 统计文件
+    markov_stats_path: String,
+    markov_threshold: usize,
+    /// PRINCE 模式下每个候选由几个词组成
+    prince_min_elements: usize,
+    prince_max_elements: usize,
+    walk_layout: KeyboardLayout,
+    /// 键盘行走最多改变几次方向
+    walk_turns: usize,
+    /// 拼接在每个行走之后的掩码，为空时只尝试行走本身
+    walk_mask: String,
+    /// 日期范围，YYYY-MM-DD
+    date_from: String,
+    date_to: String,
+    /// 每行一个日期格式，为空时使用内置的常见格式
+    date_formats: String,
+    rules_text: String,
     pub(crate) jwt_decoded_payload: String,
     pub(crate) jwt_decoded_header: String,
     pub(crate) jwt_burp_token: String,
     pub(crate) jwt_singed_token: String,
+    pub(crate) claim_warnings: Vec<String>,
 
     pub(crate) burped_key_start: String,
     pub(crate) burped_key: String,
     pub(crate) burped_key_end: String,
+    /// 候选依次按这些方式解读后校验
+    key_encodings: Vec<KeyEncoding>,
+    /// 找到的密钥是按哪种方式解读的，重新签名时使用
+    pub(crate) found_encoding: KeyEncoding,
+    /// 找到的完整候选的原始字节，可能不是UTF-8
+    found_secret: Vec<u8>,
 
-    pub(crate) error_type: ErrorType,
-    task_handle: Option<thread::JoinHandle<Option<String>>>,
-    tx: Option<Sender<String>>,
-    rx: Option<Receiver<String>>,
-    stop_tx: Option<Sender<()>>,
+    task_handle: Option<thread::JoinHandle<jwt_cracker::Result<Option<Found>>>>,
+    rx: Option<Receiver<Event>>,
+    stats: Option<Stats>,
+    cancel: Option<CancelToken>,
     show_about_window: bool,
+    forge_window: ForgeWindow,
+    audit_window: AuditWindow,
+    oracle_window: OracleWindow,
+}
+
+/// 本机可用的线程数
+fn max_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
 }
 
 /// 应用程序运行状态枚举
@@ -49,30 +111,102 @@ enum RunningStatus {
     #[default]
     OK,
     Running,
-    Error,
+    Error(ErrorType),
     Stopping,
     Stopped,
     Found,
+    NotFound,
+    LimitReached,
+}
+
+/// 界面上可选的攻击模式
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+enum AttackKind {
+    #[default]
+    BruteForce,
+    Markov,
+    Mask,
+    Wordlist,
+    Rules,
+    Hybrid,
+    Prince,
+    KeyboardWalk,
+    Dates,
+}
+
+impl AttackKind {
+    const ALL: [AttackKind; 9] = [
+        AttackKind::BruteForce,
+        AttackKind::Markov,
+        AttackKind::Mask,
+        AttackKind::Wordlist,
+        AttackKind::Rules,
+        AttackKind::Hybrid,
+        AttackKind::Prince,
+        AttackKind::KeyboardWalk,
+        AttackKind::Dates,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            AttackKind::BruteForce => "暴力破解",
+            AttackKind::Markov => "Markov 暴力破解",
+            AttackKind::Mask => "掩码",
+            AttackKind::Wordlist => "字典",
+            AttackKind::Rules => "字典+规则",
+            AttackKind::Hybrid => "字典+掩码",
+            AttackKind::Prince => "PRINCE",
+            AttackKind::KeyboardWalk => "键盘行走",
+            AttackKind::Dates => "日期",
+        }
+    }
 }
 
 /// 错误类型枚举
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub(crate) enum ErrorType {
-    None,
     UserCharsetEmpty,
     JwtTokenEmpty,
-    JwtTokenFormatError,
     UnknownAlgorithmType,
     InputJSONFormatError,
     SignJWTFailed,
+    /// 爆破线程 panic
+    WorkerPanicked,
+    /// 库返回的错误，直接显示它的信息
+    Library(String),
 }
 
 impl Default for MainWindow {
     fn default() -> Self {
         Self {
-            stop_tx: None,
+            cancel: None,
             status: RunningStatus::default(),
             use_user_charset: false,
+            length_in_bytes: false,
+            user_charset_bytes: false,
+            attack_kind: AttackKind::default(),
+            try_common_first: false,
+            threads: max_threads(),
+            low_priority: false,
+            pin_cores: false,
+            max_secs: 0,
+            max_candidates: 0,
+            job_path: "job.toml".to_string(),
+            job_message: None,
+            mask: "?l?l?l?d?d".to_string(),
+            mask_first: false,
+            wordlist_path: "".to_string(),
+            markov_stats_path: "markov.json".to_string(),
+            markov_threshold: 0,
+            prince_min_elements: 1,
+            prince_max_elements: 4,
+            walk_layout: KeyboardLayout::Qwerty,
+            walk_turns: 2,
+            walk_mask: String::new(),
+            date_from: "1970-01-01".to_string(),
+            date_to: "2030-12-31".to_string(),
+            date_formats: "".to_string(),
+            rules_text: ":\nc\nu\n$1\n$!".to_string(),
             user_charset: "".to_string(),
             min_len: 1,
             max_len: 4,
@@ -84,25 +218,37 @@ impl Default for MainWindow {
             jwt_decoded_header: "".to_string(),
             jwt_burp_token: "".to_string(),
             jwt_singed_token: "".to_string(),
+            claim_warnings: Vec::new(),
             burped_key_start: "".to_string(),
             burped_key: "".to_string(),
+            key_encodings: vec![KeyEncoding::Raw],
+            found_encoding: KeyEncoding::Raw,
+            found_secret: Vec::new(),
             burped_key_end: "".to_string(),
-            error_type: ErrorType::None,
             task_handle: None,
-            tx: None,
+            stats: None,
             rx: None,
             show_about_window: false,
+            forge_window: ForgeWindow::default(),
+            audit_window: AuditWindow::default(),
+            oracle_window: OracleWindow::default(),
         }
     }
 }
 
 impl MainWindow {
-    fn handle_channels(&mut self, ctx: &egui::Context) {
+    fn drain_events(&mut self, ctx: &egui::Context) {
         if let Some(rx) = &self.rx {
             loop {
                 match rx.try_recv() {
-                    Ok(key) => {
-                        self.burped_key = key;
+                    Ok(event) => {
+                        if let Some(stats) = event.stats() {
+                            self.burped_key = self.strip_affixes(stats.sample_lossy());
+                            self.stats = Some(stats.clone());
+                        }
+                        if matches!(event, Event::LimitReached(_)) {
+                            self.status = RunningStatus::LimitReached;
+                        }
                         ctx.request_repaint();
                     }
                     Err(TryRecvError::Empty) => break,
@@ -113,77 +259,156 @@ impl MainWindow {
                 }
             }
         }
+    }
 
-        if let Some(handle) = self.task_handle.as_mut() {
-            if handle.is_finished() {
-                match self.task_handle.take().unwrap().join() {
-                    Ok(Some(key)) => {
-                        self.burped_key = key;
-                        self.status = RunningStatus::Found;
-                        encode_jwt(self);
-                    }
-                    Ok(None) => {
-                        if self.error_type == ErrorType::None {
-                            if self.status == RunningStatus::Stopped {
-                                self.status = RunningStatus::OK;
-                            }
-                        };
-                        if (self.status == RunningStatus::Stopping) {
-                            self.status = RunningStatus::Stopped;
-                        } else {
-                            self.status = RunningStatus::Error;
-                            self.error_type = ErrorType::None;
-                        }
-                    }
-                    Err(_) => {
-                        self.status = RunningStatus::Error;
-                        self.error_type = ErrorType::None;
+    fn handle_channels(&mut self, ctx: &egui::Context) {
+        self.drain_events(ctx);
+        if let Some(handle) = self.task_handle.as_mut()
+            && handle.is_finished()
+        {
+            // 线程在上面取完之后、结束之前可能还发了事件，例如 LimitReached
+            self.drain_events(ctx);
+            match self.task_handle.take().unwrap().join() {
+                Ok(Ok(Some(found))) => {
+                    self.burped_key = self.strip_affixes(found.secret_lossy());
+                    self.found_encoding = found.encoding;
+                    self.found_secret = found.secret.clone();
+                    self.status = RunningStatus::Found;
+                    encode_jwt(self);
+                }
+                Ok(Ok(None)) => {
+                    if self.status == RunningStatus::Stopping {
+                        self.status = RunningStatus::Stopped;
+                    } else if self.status != RunningStatus::LimitReached {
+                        self.status = RunningStatus::NotFound;
                     }
                 }
+                Ok(Err(e)) => {
+                    self.status = RunningStatus::Error(ErrorType::Library(e.to_string()));
+                }
+                Err(_) => {
+                    self.status = RunningStatus::Error(ErrorType::WorkerPanicked);
+                }
             }
         }
     }
 
     fn render_central_panel(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.heading("选择字符集:");
-            ui.checkbox(&mut self.use_user_charset, "自定义字符集");
+            ui.heading("攻击模式:");
+            ComboBox::from_id_salt("attack_kind")
+                .selected_text(self.attack_kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in AttackKind::ALL {
+                        ui.selectable_value(&mut self.attack_kind, kind, kind.label());
+                    }
+                });
+            ui.checkbox(&mut self.try_common_first, "先试常见密钥");
         });
-        ui.horizontal(|ui| {
-            if self.use_user_charset {
-                ui.group(|child_ui| {
-                    child_ui.add(
-                        TextEdit::singleline(&mut self.user_charset)
-                            .hint_text("请输入自定义字符集"),
+        match self.attack_kind {
+            AttackKind::BruteForce => self.render_charset_options(ui),
+            AttackKind::Markov => {
+                self.render_charset_options(ui);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut self.markov_stats_path)
+                            .desired_width(ui.available_width() - 120.0)
+                            .hint_text("统计文件路径"),
                     )
+                    .on_hover_text("用 JwtCrackerCLI train -w <字典> 生成");
+                    ui.label("每位前");
+                    ui.add(DragValue::new(&mut self.markov_threshold))
+                        .on_hover_text("每一位只取最可能的这么多个字符，0 为不裁剪");
+                    ui.label("个");
                 });
-            } else {
-                ui.group(|child_ui| {
-                    child_ui
-                        .add(Checkbox::new(&mut self.use_digits, "数字"))
-                        .on_hover_text("0123456789");
-                    child_ui
-                        .add(Checkbox::new(&mut self.use_lowercase, "小写字母"))
-                        .on_hover_text("abcdefghijklmnopqrstuvwxyz");
-                    child_ui
-                        .add(Checkbox::new(&mut self.use_uppercase, "大写字母"))
-                        .on_hover_text("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
-                    child_ui
-                        .add(Checkbox::new(&mut self.use_special, "特殊字符"))
-                        .on_hover_text("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~");
+            }
+            AttackKind::Mask => self.render_mask_input(ui),
+            AttackKind::Wordlist => self.render_wordlist_input(ui),
+            AttackKind::Rules => {
+                self.render_wordlist_input(ui);
+                ui.add(
+                    TextEdit::multiline(&mut self.rules_text)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(3)
+                        .desired_width(ui.available_width()),
+                )
+                .on_hover_text("每行一条 hashcat 风格的规则");
+            }
+            AttackKind::Hybrid => {
+                self.render_wordlist_input(ui);
+                ui.horizontal(|ui| {
+                    self.render_mask_input(ui);
+                    ui.checkbox(&mut self.mask_first, "掩码在前");
                 });
             }
-        });
+            AttackKind::Prince => {
+                self.render_wordlist_input(ui);
+                ui.horizontal(|ui| {
+                    ui.label("每个候选由");
+                    ui.add(DragValue::new(&mut self.prince_min_elements).range(1..=16));
+                    ui.label("到");
+                    ui.add(DragValue::new(&mut self.prince_max_elements).range(1..=16));
+                    ui.label("个词组成");
+                    if self.prince_min_elements > self.prince_max_elements {
+                        self.prince_min_elements = self.prince_max_elements;
+                    }
+                });
+            }
+            AttackKind::KeyboardWalk => {
+                ui.horizontal(|ui| {
+                    ui.label("键盘布局:");
+                    ui.radio_value(&mut self.walk_layout, KeyboardLayout::Qwerty, "QWERTY");
+                    ui.radio_value(&mut self.walk_layout, KeyboardLayout::Azerty, "AZERTY");
+                    ui.label("最多转弯");
+                    ui.add(DragValue::new(&mut self.walk_turns).range(0..=8))
+                        .on_hover_text("改变方向或平行再走一段（如 1qaz2wsx）各算一次");
+                    ui.label("次");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("后接掩码:");
+                    ui.add(TextEdit::singleline(&mut self.walk_mask).hint_text("?d?d?d"))
+                        .on_hover_text("例如 ?d?d?d 可以得到 qwerty123，留空只尝试行走本身");
+                });
+            }
+            AttackKind::Dates => {
+                ui.horizontal(|ui| {
+                    ui.label("日期从");
+                    ui.add(TextEdit::singleline(&mut self.date_from).desired_width(90.0));
+                    ui.label("到");
+                    ui.add(TextEdit::singleline(&mut self.date_to).desired_width(90.0));
+                });
+                ui.add(
+                    TextEdit::multiline(&mut self.date_formats)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(2)
+                        .desired_width(ui.available_width())
+                        .hint_text("每行一个格式，例如 YYYYMMDD、DD-MM-YYYY，留空使用常见格式"),
+                );
+            }
+        }
         ui.horizontal(|ui| {
-            ui.group(|chile_ui| {
-                chile_ui.add(Label::new("爆破长度:从"));
-                chile_ui.add(DragValue::new(&mut self.min_len));
-                chile_ui.add(Label::new("到"));
-                chile_ui.add(DragValue::new(&mut self.max_len));
-                if self.min_len > self.max_len {
-                    self.min_len = self.max_len;
-                }
-            });
+            if matches!(
+                self.attack_kind,
+                AttackKind::BruteForce
+                    | AttackKind::Markov
+                    | AttackKind::Prince
+                    | AttackKind::KeyboardWalk
+            ) {
+                ui.group(|chile_ui| {
+                    chile_ui.add(Label::new("爆破长度:从"));
+                    chile_ui.add(DragValue::new(&mut self.min_len));
+                    chile_ui.add(Label::new("到"));
+                    chile_ui.add(DragValue::new(&mut self.max_len));
+                    if self.min_len > self.max_len {
+                        self.min_len = self.max_len;
+                    }
+                    if self.attack_kind == AttackKind::BruteForce {
+                        chile_ui
+                            .checkbox(&mut self.length_in_bytes, "按字节")
+                            .on_hover_text("包含中文等多字节字符时，按 UTF-8 字节数限制长度");
+                    }
+                });
+            }
             if (self.status == RunningStatus::OK) | (self.status == RunningStatus::Stopped) {
                 ui.group(|chile_ui| {
                     chile_ui
@@ -215,6 +440,7 @@ impl MainWindow {
                 );
             }
         });
+        self.render_run_settings(ui);
         ui.separator();
 
         let mut layouter = |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
@@ -223,7 +449,7 @@ impl MainWindow {
                 ui.style(),
                 &CodeTheme::from_memory(ui.ctx(), ui.style()),
                 buf.as_str(),
-                "json".into(),
+                "json",
             );
             layout_job.wrap.max_width = wrap_width;
             ui.fonts(|f| f.layout_job(layout_job))
@@ -245,6 +471,10 @@ impl MainWindow {
             {
                 decode_jwt(self);
             };
+            for warning in &self.claim_warnings {
+                jwt_ui
+                    .label(RichText::new(format!("提示: {}", warning)).color(egui::Color32::GRAY));
+            }
 
             // 使用 with_columns 方法创建两列，每列平分宽度
             jwt_ui.columns(2, |columns| {
@@ -282,6 +512,7 @@ impl MainWindow {
                     encode_jwt(self);
                 };
             });
+            self.render_claim_helpers(jwt_ui);
 
             jwt_ui
                 .add(
@@ -297,29 +528,406 @@ impl MainWindow {
         });
     }
 
+    fn render_run_settings(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("运行设置").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("线程数:");
+                ui.add(Slider::new(&mut self.threads, 1..=max_threads()));
+                ui.checkbox(&mut self.low_priority, "低优先级")
+                    .on_hover_text("以最低优先级运行，避免影响其它程序");
+                ui.checkbox(&mut self.pin_cores, "绑定CPU核心");
+            });
+            ui.horizontal(|ui| {
+                ui.label("时间上限(秒):");
+                ui.add(DragValue::new(&mut self.max_secs))
+                    .on_hover_text("0 表示不限制");
+                ui.label("候选数量上限:");
+                ui.add(DragValue::new(&mut self.max_candidates).speed(1000))
+                    .on_hover_text("0 表示不限制");
+            });
+            ui.horizontal(|ui| {
+                ui.label("候选解读为:")
+                    .on_hover_text("有些框架会把配置的密钥先按 Base64 解码再作为HMAC密钥");
+                for encoding in KeyEncoding::ALL {
+                    let mut enabled = self.key_encodings.contains(&encoding);
+                    if ui.checkbox(&mut enabled, encoding.name()).changed() {
+                        if enabled {
+                            self.key_encodings.push(encoding);
+                        } else {
+                            self.key_encodings.retain(|e| *e != encoding);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("任务文件:");
+                ui.add(TextEdit::singleline(&mut self.job_path).hint_text("job.toml"))
+                    .on_hover_text("扩展名为 .json 时使用 JSON 格式，否则使用 TOML 格式");
+                if ui.button("加载").clicked() {
+                    self.load_job();
+                }
+                if ui.button("保存").clicked() {
+                    self.save_job();
+                }
+                if let Some(message) = &self.job_message {
+                    ui.label(message.clone());
+                }
+            });
+        });
+    }
+
+    fn load_job(&mut self) {
+        self.job_message = Some(match Job::load(self.job_path.trim()) {
+            Ok(job) => {
+                if self.apply_job(job) {
+                    RichText::new("已加载").color(egui::Color32::DARK_GREEN)
+                } else {
+                    RichText::new("已加载，界面只支持一个令牌和一个攻击阶段，其余内容已忽略")
+                        .color(egui::Color32::YELLOW)
+                }
+            }
+            Err(e) => RichText::new(e.to_string()).color(egui::Color32::RED),
+        });
+    }
+
+    fn save_job(&mut self) {
+        self.job_message = Some(match self.job().save(self.job_path.trim()) {
+            Ok(()) => RichText::new("已保存").color(egui::Color32::DARK_GREEN),
+            Err(e) => RichText::new(e.to_string()).color(egui::Color32::RED),
+        });
+    }
+
+    /// 当前界面上的设置对应的任务
+    fn job(&self) -> Job {
+        let token = self.jwt_burp_token.trim();
+        Job {
+            tokens: if token.is_empty() {
+                Vec::new()
+            } else {
+                vec![token.to_string()]
+            },
+            options: self.run_options(),
+            output: JobOutput::default(),
+            attack: self.attack_config(),
+        }
+    }
+
+    /// 把任务中的设置填到界面上，任务中有界面无法表示的内容时返回 `false`
+    fn apply_job(&mut self, job: Job) -> bool {
+        let mut complete = job.tokens.len() <= 1;
+        if let Some(token) = job.tokens.into_iter().next() {
+            self.jwt_burp_token = token;
+            decode_jwt(self);
+        }
+
+        let options = job.options;
+        self.threads = options
+            .threads
+            .unwrap_or(max_threads())
+            .clamp(1, max_threads());
+        self.low_priority = options.low_priority;
+        self.pin_cores = options.pin_cores;
+        self.max_secs = options.max_time.map_or(0, |time| time.as_secs());
+        self.max_candidates = options.max_candidates.unwrap_or(0);
+
+        let attack = job.attack;
+        self.burped_key_start = attack.prefix;
+        self.burped_key_end = attack.suffix;
+        self.key_encodings = if attack.key_encodings.is_empty() {
+            vec![KeyEncoding::Raw]
+        } else {
+            attack.key_encodings
+        };
+        self.try_common_first = attack.stages.contains(&AttackMode::Common);
+        let mut stages = attack
+            .stages
+            .into_iter()
+            .filter(|stage| *stage != AttackMode::Common);
+        match stages.next() {
+            Some(AttackMode::BruteForce {
+                charset,
+                min_length,
+                max_length,
+                length_unit,
+            }) => {
+                self.attack_kind = AttackKind::BruteForce;
+                self.min_len = min_length;
+                self.max_len = max_length;
+                self.length_in_bytes = length_unit == LengthUnit::Bytes;
+                self.apply_charset(charset);
+            }
+            Some(AttackMode::Markov {
+                charset,
+                min_length,
+                max_length,
+                stats,
+                threshold,
+            }) => {
+                self.attack_kind = AttackKind::Markov;
+                self.min_len = min_length;
+                self.max_len = max_length;
+                self.markov_stats_path = stats.display().to_string();
+                self.markov_threshold = threshold;
+                self.apply_charset(charset);
+            }
+            Some(AttackMode::Mask {
+                mask,
+                custom_charsets,
+            }) => {
+                self.attack_kind = AttackKind::Mask;
+                self.mask = mask;
+                complete &= custom_charsets.is_empty();
+            }
+            Some(AttackMode::Wordlist { path }) => {
+                self.attack_kind = AttackKind::Wordlist;
+                self.wordlist_path = path.display().to_string();
+            }
+            Some(AttackMode::Rules { path, rules }) => {
+                self.attack_kind = AttackKind::Rules;
+                self.wordlist_path = path.display().to_string();
+                self.rules_text = rules.join("\n");
+            }
+            Some(AttackMode::Hybrid {
+                path,
+                mask,
+                mask_first,
+            }) => {
+                self.attack_kind = AttackKind::Hybrid;
+                self.wordlist_path = path.display().to_string();
+                self.mask = mask;
+                self.mask_first = mask_first;
+            }
+            Some(AttackMode::Prince {
+                path,
+                min_length,
+                max_length,
+                min_elements,
+                max_elements,
+            }) => {
+                self.attack_kind = AttackKind::Prince;
+                self.wordlist_path = path.display().to_string();
+                self.min_len = min_length;
+                self.max_len = max_length;
+                self.prince_min_elements = min_elements;
+                self.prince_max_elements = max_elements;
+            }
+            Some(AttackMode::KeyboardWalk {
+                layout,
+                min_length,
+                max_length,
+                max_turns,
+                mask,
+            }) => {
+                self.attack_kind = AttackKind::KeyboardWalk;
+                self.walk_mask = mask.unwrap_or_default();
+                self.walk_layout = layout;
+                self.min_len = min_length;
+                self.max_len = max_length;
+                self.walk_turns = max_turns;
+            }
+            Some(AttackMode::Dates {
+                start,
+                end,
+                formats,
+            }) => {
+                self.attack_kind = AttackKind::Dates;
+                self.date_from = start;
+                self.date_to = end;
+                self.date_formats = formats.join("\n");
+            }
+            Some(AttackMode::Common) | None => {}
+            Some(AttackMode::Stdin) => complete = false,
+        }
+        complete && stages.next().is_none()
+    }
+
+    fn apply_charset(&mut self, charset: Charset) {
+        self.use_user_charset = false;
+        self.user_charset_bytes = false;
+        match charset {
+            Charset::Default => {
+                self.use_lowercase = true;
+                self.use_uppercase = true;
+                self.use_digits = true;
+                self.use_special = false;
+            }
+            Charset::Options {
+                lowercase,
+                uppercase,
+                digits,
+                special,
+            } => {
+                self.use_lowercase = lowercase;
+                self.use_uppercase = uppercase;
+                self.use_digits = digits;
+                self.use_special = special;
+            }
+            Charset::Custom(charset) => {
+                self.use_user_charset = true;
+                self.user_charset = charset;
+            }
+            Charset::Bytes(spec) => {
+                self.use_user_charset = true;
+                self.user_charset_bytes = true;
+                self.user_charset = spec;
+            }
+        }
+    }
+
+    fn render_charset_options(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("选择字符集:");
+            ui.checkbox(&mut self.use_user_charset, "自定义字符集");
+            if self.use_user_charset {
+                ui.checkbox(&mut self.user_charset_bytes, "按字节范围解析")
+                    .on_hover_text("例如 \\x00-\\xff，用于爆破二进制密钥");
+            }
+        });
+        ui.horizontal(|ui| {
+            if self.use_user_charset {
+                ui.group(|child_ui| {
+                    child_ui.add(
+                        TextEdit::singleline(&mut self.user_charset)
+                            .hint_text("请输入自定义字符集"),
+                    )
+                });
+                if !self.user_charset_bytes {
+                    ui.label(format!("{} 个字符", self.charset().chars().chars().count()));
+                }
+            } else {
+                ui.group(|child_ui| {
+                    child_ui
+                        .add(Checkbox::new(&mut self.use_digits, "数字"))
+                        .on_hover_text("0123456789");
+                    child_ui
+                        .add(Checkbox::new(&mut self.use_lowercase, "小写字母"))
+                        .on_hover_text("abcdefghijklmnopqrstuvwxyz");
+                    child_ui
+                        .add(Checkbox::new(&mut self.use_uppercase, "大写字母"))
+                        .on_hover_text("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+                    child_ui
+                        .add(Checkbox::new(&mut self.use_special, "特殊字符"))
+                        .on_hover_text("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~");
+                });
+            }
+        });
+        for warning in self.charset().warnings() {
+            ui.label(RichText::new(format!("警告: {}", warning)).color(egui::Color32::YELLOW));
+        }
+    }
+
+    fn render_mask_input(&mut self, ui: &mut egui::Ui) {
+        ui.add(TextEdit::singleline(&mut self.mask).hint_text("?u?l?l?d?d"))
+            .on_hover_text("?l 小写 ?u 大写 ?d 数字 ?s 特殊字符 ?a 全部 ?b 全部字节");
+    }
+
+    fn render_wordlist_input(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            TextEdit::singleline(&mut self.wordlist_path)
+                .desired_width(ui.available_width())
+                .hint_text("字典文件路径"),
+        );
+    }
+
+    /// 时间声明的可读显示和一键修改、提权模板
+    fn render_claim_helpers(&mut self, ui: &mut egui::Ui) {
+        let Ok(mut payload) = from_str::<Value>(&self.jwt_decoded_payload) else {
+            return;
+        };
+        let mut edit = None;
+        ui.horizontal_wrapped(|ui| {
+            for (name, date) in describe_time_claims(&payload) {
+                ui.label(RichText::new(format!("{}: {}", name, date)).color(egui::Color32::GRAY));
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("exp +1年").clicked() {
+                edit = Some(ClaimEdit::ExtendExp(SECS_PER_YEAR));
+            }
+            if ui.button("iat=现在").clicked() {
+                edit = Some(ClaimEdit::SetNow("iat"));
+            }
+            if ui.button("nbf=现在").clicked() {
+                edit = Some(ClaimEdit::SetNow("nbf"));
+            }
+            if ui.button("删除exp").clicked() {
+                edit = Some(ClaimEdit::Remove("exp"));
+            }
+            ui.menu_button("提权模板", |menu_ui| {
+                for (index, (label, _)) in ESCALATION_TEMPLATES.iter().enumerate() {
+                    if menu_ui.button(*label).clicked() {
+                        edit = Some(ClaimEdit::Escalate(index));
+                        menu_ui.close();
+                    }
+                }
+            });
+        });
+
+        if let Some(edit) = edit
+            && apply_claim_edit(&mut payload, edit)
+        {
+            self.claim_warnings = time_claim_warnings(&payload)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            if let Ok(pretty) = serde_json::to_string_pretty(&payload) {
+                self.jwt_decoded_payload = pretty;
+            }
+            encode_jwt(self);
+        }
+    }
+
     fn render_bottom_panel(&mut self, ui: &mut egui::Ui) {
-        let status_text = match self.status {
+        let status_text = match &self.status {
             RunningStatus::OK => RichText::new("准备就绪").color(egui::Color32::DARK_GREEN),
-            RunningStatus::Running => RichText::new("Burping...").color(egui::Color32::BLACK),
-            RunningStatus::Error => {
-                let error_message = match self.error_type {
-                    ErrorType::None => "未知错误",
+            RunningStatus::Running => {
+                let progress = self.stats.as_ref().map(|stats| {
+                    let percent = stats
+                        .percent()
+                        .map(|p| format!(" {:.2}%", p))
+                        .unwrap_or_default();
+                    format!(" {} 个 {:.0}/s{}", stats.tried, stats.rate(), percent)
+                });
+                RichText::new(format!("Burping...{}", progress.unwrap_or_default()))
+                    .color(egui::Color32::BLACK)
+            }
+            RunningStatus::Error(error_type) => {
+                let error_message = match error_type {
                     ErrorType::UserCharsetEmpty => "用户字符集不能为空",
                     ErrorType::JwtTokenEmpty => "预想爆破字段不能为空",
-                    ErrorType::JwtTokenFormatError => "JWT格式错误",
                     ErrorType::UnknownAlgorithmType => "未知加密方式",
                     ErrorType::InputJSONFormatError => "Json格式有误",
                     ErrorType::SignJWTFailed => "JWT签名失败",
+                    ErrorType::WorkerPanicked => "爆破线程异常退出",
+                    ErrorType::Library(message) => message,
                 };
                 RichText::new(error_message).color(egui::Color32::RED)
             }
             RunningStatus::Stopping => RichText::new("正在停止...").color(egui::Color32::YELLOW),
             RunningStatus::Stopped => RichText::new("已经停止").color(egui::Color32::GRAY),
+            RunningStatus::NotFound => {
+                RichText::new("在给定的范围内未找到有效密钥").color(egui::Color32::GRAY)
+            }
+            RunningStatus::LimitReached => {
+                let position = self.stats.as_ref().map_or(0, |stats| stats.position());
+                RichText::new(format!("已达到上限，尝试到第 {} 个候选", position))
+                    .color(egui::Color32::GRAY)
+            }
             RunningStatus::Found => {
                 let mut result = String::new(); // 创建一个可变的新字符串
                 result.push_str(&self.burped_key_start); // 追加第一个字符串切片
                 result.push_str(&self.burped_key); // 追加第二个字符串切片
                 result.push_str(&self.burped_key_end); // 追加第三个字符串切片
+                if self.found_encoding != KeyEncoding::Raw {
+                    result.push_str(&format!(" (按 {} 解码)", self.found_encoding.name()));
+                }
+                let found = Found {
+                    secret: self.found_secret.clone(),
+                    encoding: self.found_encoding,
+                };
+                if !found.is_printable() {
+                    result.push_str(&format!(" (十六进制: {})", found.secret_hex()));
+                }
                 RichText::new(format!("密钥已找到: {}", result)).color(egui::Color32::BLUE)
             }
         };
@@ -331,7 +939,12 @@ impl MainWindow {
                 ui.add(Spinner::new().speed(2.0).clockwise(true));
             }
             if self.status == RunningStatus::Stopping {
-                ui.add(Spinner::new().speed(3.0).clockwise(false));
+                ui.add(
+                    Spinner::new()
+                        .speed(3.0)
+                        .clockwise(false)
+                        .color(egui::Color32::YELLOW),
+                );
             }
             ui.add(Label::new(status_text));
 
@@ -365,90 +978,210 @@ impl MainWindow {
 
     fn start_bruteforce_task(&mut self, ctx: egui::Context) {
         if self.jwt_burp_token.is_empty() {
-            self.status = RunningStatus::Error;
-            self.error_type = ErrorType::JwtTokenEmpty;
-            return;
-        }
-        if self.use_user_charset && self.user_charset.is_empty() {
-            self.status = RunningStatus::Error;
-            self.error_type = ErrorType::UserCharsetEmpty;
+            self.status = RunningStatus::Error(ErrorType::JwtTokenEmpty);
             return;
         }
-        if !self.use_user_charset
-            && !self.use_digits
-            && !self.use_lowercase
-            && !self.use_uppercase
-            && !self.use_special
-        {
-            self.status = RunningStatus::Error;
-            self.error_type = ErrorType::UserCharsetEmpty;
+        let config = self.attack_config();
+        if config.stages.iter().any(
+            |stage| matches!(stage, AttackMode::BruteForce { charset, .. } | AttackMode::Markov { charset, .. } if charset.is_empty()),
+        ) {
+            self.status = RunningStatus::Error(ErrorType::UserCharsetEmpty);
             return;
         }
+        let options = self.run_options();
+        let (cracker, source) = match Cracker::new(&self.jwt_burp_token).and_then(|cracker| {
+            let cracker = cracker
+                .options(options)
+                .key_encodings(config.key_encodings.clone());
+            Ok((cracker, config.build_source()?))
+        }) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.status = RunningStatus::Error(ErrorType::Library(e.to_string()));
+                return;
+            }
+        };
 
-        let jwt_token = self.jwt_burp_token.clone();
-
-        let (tx, rx) = unbounded::<String>();
-        let (stop_tx, stop_rx) = unbounded::<()>();
-        self.tx = Some(tx.clone());
+        let (tx, rx) = unbounded::<Event>();
+        let cancel = CancelToken::new();
         self.rx = Some(rx);
-        self.stop_tx = Some(stop_tx);
-
-        let stop_rx_arc = Arc::new(stop_rx);
-        let optional_stop_rx = Some(stop_rx_arc);
-
-        let generator = if self.use_user_charset {
-            CombinationGenerator::new_with_charset(
-                self.min_len,
-                self.max_len,
-                &*self.user_charset,
-                optional_stop_rx,
-            )
-        } else {
-            CombinationGenerator::new_with_options(
-                self.min_len,
-                self.max_len,
-                self.use_lowercase,
-                self.use_uppercase,
-                self.use_digits,
-                self.use_special,
-                optional_stop_rx,
-            )
-        };
-        let burped_key_start = self.burped_key_start.clone();
-        let burped_key_end = self.burped_key_end.clone();
+        self.stats = None;
+        self.cancel = Some(cancel.clone());
+        self.found_encoding = KeyEncoding::Raw;
+        self.found_secret.clear();
+
         self.task_handle = Some(thread::spawn(move || {
-            let found_key = generator.par_bridge().find_any(|key| {
-                let new_key = format!("{}{}{}", burped_key_start, key, burped_key_end);
-                let _ = tx.send(key.to_string());
-                verify_jwt_hs256_token(jwt_token.as_str(), new_key.as_str()).is_some()
+            let found = cracker.run(source, &cancel, |event| {
+                let _ = tx.send(event.clone());
+                ctx.request_repaint();
             });
             ctx.request_repaint();
-            found_key.map(|s| s.to_owned())
+            found
         }));
         self.status = RunningStatus::Running;
     }
 
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            threads: Some(self.threads),
+            low_priority: self.low_priority,
+            pin_cores: self.pin_cores,
+            max_time: (self.max_secs > 0).then(|| Duration::from_secs(self.max_secs)),
+            max_candidates: (self.max_candidates > 0).then_some(self.max_candidates),
+            skip: 0,
+        }
+    }
+
+    fn charset(&self) -> Charset {
+        if self.use_user_charset && self.user_charset_bytes {
+            Charset::Bytes(self.user_charset.clone())
+        } else if self.use_user_charset {
+            Charset::Custom(self.user_charset.clone())
+        } else {
+            Charset::Options {
+                lowercase: self.use_lowercase,
+                uppercase: self.use_uppercase,
+                digits: self.use_digits,
+                special: self.use_special,
+            }
+        }
+    }
+
+    /// 根据界面上的选项生成攻击配置
+    fn attack_config(&self) -> AttackConfig {
+        let path = PathBuf::from(self.wordlist_path.trim());
+        let stage = match self.attack_kind {
+            AttackKind::BruteForce => AttackMode::BruteForce {
+                charset: self.charset(),
+                min_length: self.min_len,
+                max_length: self.max_len,
+                length_unit: if self.length_in_bytes {
+                    LengthUnit::Bytes
+                } else {
+                    LengthUnit::Chars
+                },
+            },
+            AttackKind::Markov => AttackMode::Markov {
+                charset: self.charset(),
+                min_length: self.min_len,
+                max_length: self.max_len,
+                stats: PathBuf::from(self.markov_stats_path.trim()),
+                threshold: self.markov_threshold,
+            },
+            AttackKind::Mask => AttackMode::Mask {
+                mask: self.mask.clone(),
+                custom_charsets: Vec::new(),
+            },
+            AttackKind::Wordlist => AttackMode::Wordlist { path },
+            AttackKind::Rules => AttackMode::Rules {
+                path,
+                rules: self.rules_text.lines().map(str::to_string).collect(),
+            },
+            AttackKind::Hybrid => AttackMode::Hybrid {
+                path,
+                mask: self.mask.clone(),
+                mask_first: self.mask_first,
+            },
+            AttackKind::Prince => AttackMode::Prince {
+                path,
+                min_length: self.min_len,
+                max_length: self.max_len,
+                min_elements: self.prince_min_elements,
+                max_elements: self.prince_max_elements,
+            },
+            AttackKind::KeyboardWalk => AttackMode::KeyboardWalk {
+                layout: self.walk_layout,
+                min_length: self.min_len,
+                max_length: self.max_len,
+                max_turns: self.walk_turns,
+                mask: (!self.walk_mask.trim().is_empty())
+                    .then(|| self.walk_mask.trim().to_string()),
+            },
+            AttackKind::Dates => AttackMode::Dates {
+                start: self.date_from.trim().to_string(),
+                end: self.date_to.trim().to_string(),
+                formats: self
+                    .date_formats
+                    .lines()
+                    .map(str::trim)
+                    .filter(|format| !format.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+        };
+        let mut stages = vec![stage];
+        if self.try_common_first {
+            stages.insert(0, AttackMode::Common);
+        }
+        AttackConfig {
+            stages,
+            prefix: self.burped_key_start.clone(),
+            suffix: self.burped_key_end.clone(),
+            key_encodings: self.key_encodings.clone(),
+        }
+    }
+
+    /// 包含前后缀的完整候选
+    fn full_key(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.burped_key_start, self.burped_key, self.burped_key_end
+        )
+    }
+
+    /// 按找到密钥时的解读方式得到的HMAC密钥，无法解码时按原样使用
+    ///
+    /// 密钥未被修改时使用找到的原始字节，避免非UTF-8的密钥在显示时被替换
+    fn hmac_key(&self, key: &str) -> Vec<u8> {
+        let bytes = if !self.found_secret.is_empty()
+            && String::from_utf8_lossy(&self.found_secret) == key
+        {
+            self.found_secret.as_slice()
+        } else {
+            key.as_bytes()
+        };
+        self.found_encoding
+            .decode(bytes)
+            .unwrap_or_else(|| bytes.to_vec())
+    }
+
+    /// 候选中包含前后缀，界面上只显示中间爆破的部分
+    fn strip_affixes(&self, secret: String) -> String {
+        secret
+            .strip_prefix(self.burped_key_start.as_str())
+            .and_then(|rest| rest.strip_suffix(self.burped_key_end.as_str()))
+            .map(str::to_string)
+            .unwrap_or(secret)
+    }
+
     fn stop_bruteforce_task(&mut self) {
         if self.status == RunningStatus::Running {
             self.status = RunningStatus::Stopping;
 
-            if let Some(tx) = self.stop_tx.take() {
-                let _ = tx.send(());
+            if let Some(cancel) = self.cancel.take() {
+                cancel.cancel();
             }
         }
     }
 
+    /// 解码或签名成功后清掉上一次留下的错误
+    fn clear_error(&mut self) {
+        if matches!(self.status, RunningStatus::Error(_)) {
+            self.status = RunningStatus::OK;
+        }
+    }
+
     fn clear_state(&mut self) {
         self.jwt_decoded_header = "".to_string();
         self.jwt_decoded_payload = "".to_string();
         self.jwt_burp_token = "".to_string();
         self.jwt_singed_token = "".to_string();
+        self.claim_warnings.clear();
         self.burped_key = "".to_string();
         self.status = RunningStatus::OK;
-        self.error_type = ErrorType::None;
 
-        if let Some(tx) = self.stop_tx.take() {
-            let _ = tx.send(());
+        if let Some(cancel) = self.cancel.take() {
+            cancel.cancel();
         }
     }
 }
@@ -465,6 +1198,18 @@ impl eframe::App for MainWindow {
                     self.show_about_window = true;
                     menu_ui.close(); // 关闭菜单
                 }
+                if menu_ui.button("密钥伪造").clicked() {
+                    self.forge_window.open = true;
+                    menu_ui.close();
+                }
+                if menu_ui.button("安全审计").clicked() {
+                    self.audit_window.open = true;
+                    menu_ui.close();
+                }
+                if menu_ui.button("在线验证").clicked() {
+                    self.oracle_window.open = true;
+                    menu_ui.close();
+                }
                 // menu_ui.separator();
             })
         });
@@ -481,6 +1226,24 @@ impl eframe::App for MainWindow {
         if self.status == RunningStatus::Stopping {
             MainWindow::stop_bruteforce_task(self)
         }
+        if self.forge_window.open {
+            self.forge_window
+                .show(ctx, &self.jwt_decoded_header, &self.jwt_decoded_payload);
+        }
+        let found_key = (self.status == RunningStatus::Found).then(|| self.full_key());
+        if self.audit_window.open {
+            self.audit_window
+                .show(ctx, &self.jwt_burp_token, found_key.as_deref());
+        }
+        if self.oracle_window.open {
+            let hmac_key = found_key.as_ref().map(|key| self.hmac_key(key));
+            self.oracle_window.show(
+                ctx,
+                &self.jwt_burp_token,
+                hmac_key.as_deref(),
+                self.forge_window.forged_token(),
+            );
+        }
         if self.show_about_window {
             egui::Window::new("关于")
                 .open(&mut self.show_about_window)
@@ -498,43 +1261,43 @@ impl eframe::App for MainWindow {
 }
 
 pub fn decode_jwt(main_window: &mut MainWindow) {
-    let parts: Vec<&str> = main_window.jwt_burp_token.split('.').collect();
-    if parts.len() == 3 {
-        let decoded_header_result = URL_SAFE_NO_PAD.decode(parts[0]);
-        let decoded_payload_result = URL_SAFE_NO_PAD.decode(parts[1]);
-
-        if let (Ok(decoded_header_bytes), Ok(decoded_payload_bytes)) =
-            (decoded_header_result, decoded_payload_result)
-        {
-            if let Ok(header_json) = serde_json::from_slice::<Value>(&decoded_header_bytes) {
-                main_window.jwt_decoded_header = serde_json::to_string_pretty(&header_json)
-                    .unwrap_or_else(|_| String::from_utf8_lossy(&decoded_header_bytes).to_string());
-            } else {
-                main_window.jwt_decoded_header =
-                    String::from_utf8_lossy(&decoded_header_bytes).to_string();
-            }
+    let decoded = split_token(&main_window.jwt_burp_token).and_then(|parts| {
+        Ok((
+            decode_segment("header", parts.header)?,
+            decode_segment("payload", parts.payload)?,
+        ))
+    });
 
-            if let Ok(payload_json) = serde_json::from_slice::<Value>(&decoded_payload_bytes) {
-                main_window.jwt_decoded_payload = serde_json::to_string_pretty(&payload_json)
-                    .unwrap_or_else(|_| {
-                        String::from_utf8_lossy(&decoded_payload_bytes).to_string()
-                    });
-            } else {
-                main_window.jwt_decoded_payload =
-                    String::from_utf8_lossy(&decoded_payload_bytes).to_string();
-            }
-        } else {
+    match decoded {
+        Ok(((header_bytes, header_json), (payload_bytes, payload_json))) => {
+            main_window.claim_warnings = payload_json
+                .as_ref()
+                .map(|payload| {
+                    time_claim_warnings(payload)
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            main_window.jwt_decoded_header = pretty_or_lossy(&header_bytes, header_json);
+            main_window.jwt_decoded_payload = pretty_or_lossy(&payload_bytes, payload_json);
+            main_window.clear_error();
+        }
+        Err(e) => {
+            main_window.claim_warnings.clear();
             main_window.jwt_decoded_header.clear();
             main_window.jwt_decoded_payload.clear();
-            main_window.error_type = ErrorType::JwtTokenFormatError;
+            main_window.status = RunningStatus::Error(ErrorType::Library(e.to_string()));
         }
-    } else {
-        main_window.jwt_decoded_header.clear();
-        main_window.jwt_decoded_payload.clear();
-        main_window.error_type = ErrorType::JwtTokenFormatError;
     }
 }
 
+/// 能解析为JSON时格式化输出，否则按UTF-8原样显示
+fn pretty_or_lossy(bytes: &[u8], json: Option<Value>) -> String {
+    json.and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or_else(|| String::from_utf8_lossy(bytes).to_string())
+}
+
 /// 使用爆破出的密钥对JWT进行签名并更新UI
 /// 参数: main_window - 对MainWindow的可变引用
 pub fn encode_jwt(main_window: &mut MainWindow) {
@@ -548,26 +1311,59 @@ pub fn encode_jwt(main_window: &mut MainWindow) {
             "HS384" => Algorithm::HS384,
             "HS512" => Algorithm::HS512,
             _ => {
-                main_window.error_type = ErrorType::UnknownAlgorithmType;
+                main_window.status = RunningStatus::Error(ErrorType::UnknownAlgorithmType);
                 return;
             }
         };
 
         let mut header = Header::new(algorithm);
         header.typ = header_json["typ"].as_str().map(String::from);
-        let mut full_key = String::new();
-        full_key.push_str(&main_window.burped_key_start);
-        full_key.push_str(&main_window.burped_key);
-        full_key.push_str(&main_window.burped_key_end);
-
-        let encoding_key = EncodingKey::from_secret(full_key.as_bytes());
+        let full_key = main_window.full_key();
+        let encoding_key = EncodingKey::from_secret(&main_window.hmac_key(&full_key));
 
         if let Ok(token) = encode(&header, &payload_json, &encoding_key) {
             main_window.jwt_singed_token = token;
+            main_window.clear_error();
         } else {
-            main_window.error_type = ErrorType::SignJWTFailed;
+            main_window.status = RunningStatus::Error(ErrorType::SignJWTFailed);
         }
     } else {
-        main_window.error_type = ErrorType::InputJSONFormatError;
+        main_window.status = RunningStatus::Error(ErrorType::InputJSONFormatError);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_job_restores_the_same_settings() {
+        let dir = std::env::temp_dir().join(format!("jwt-cracker-gui-job-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for kind in AttackKind::ALL {
+            let window = MainWindow {
+                attack_kind: kind,
+                try_common_first: true,
+                jwt_burp_token: "eyJhbGciOiJIUzI1NiJ9.e30.sig".to_string(),
+                threads: 1,
+                max_secs: 90,
+                max_candidates: 500,
+                burped_key_start: "pre".to_string(),
+                key_encodings: vec![KeyEncoding::Raw, KeyEncoding::Base64],
+                wordlist_path: "words.txt".to_string(),
+                walk_mask: "?d?d".to_string(),
+                date_formats: "YYYYMMDD".to_string(),
+                ..MainWindow::default()
+            };
+            let job = window.job();
+            for extension in ["toml", "json"] {
+                let path = dir.join(format!("{:?}.{}", kind, extension));
+                job.save(&path).unwrap();
+                let mut restored = MainWindow::default();
+                assert!(restored.apply_job(Job::load(&path).unwrap()));
+                assert_eq!(restored.job(), job, "{}", path.display());
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
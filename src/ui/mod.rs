@@ -1,2 +1,5 @@
+mod audit_window;
+mod forge_window;
 pub mod main_window;
+mod oracle_window;
 mod widget;
@@ -0,0 +1,267 @@
+use eframe::egui;
+use egui::{Color32, ComboBox, RichText, TextEdit};
+use jwt_cracker::oracle::{
+    self, Classifier, Forgery, Oracle, RequestTemplate, TokenPlacement, Verdict,
+};
+use regex::Regex;
+use std::sync::mpsc::{Receiver, channel};
+use std::thread;
+use std::time::Duration;
+
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// 令牌位置的选项
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum PlacementKind {
+    Bearer,
+    Header,
+    Cookie,
+    Query,
+}
+
+/// 后台验证线程发回的消息
+enum OracleMessage {
+    Verdict(Verdict),
+    Finished(Option<String>),
+}
+
+/// 在线验证窗口：把当前令牌和伪造令牌发给目标接口，列出被接受的令牌
+pub(crate) struct OracleWindow {
+    pub(crate) open: bool,
+    url: String,
+    method: &'static str,
+    placement: PlacementKind,
+    param_name: String,
+    body: String,
+    accept_status: String,
+    accept_regex: String,
+    rate: f64,
+    verdicts: Vec<Verdict>,
+    error: Option<String>,
+    receiver: Option<Receiver<OracleMessage>>,
+}
+
+impl Default for OracleWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            url: "http://127.0.0.1:8080/api/me".to_string(),
+            method: "GET",
+            placement: PlacementKind::Bearer,
+            param_name: "token".to_string(),
+            body: "".to_string(),
+            accept_status: "".to_string(),
+            accept_regex: "".to_string(),
+            rate: 5.0,
+            verdicts: Vec::new(),
+            error: None,
+            receiver: None,
+        }
+    }
+}
+
+impl OracleWindow {
+    /// `forged_token` 为密钥伪造窗口的结果，不为空时一并验证
+    pub(crate) fn show(
+        &mut self,
+        ctx: &egui::Context,
+        token: &str,
+        secret: Option<&[u8]>,
+        forged_token: &str,
+    ) {
+        self.handle_messages();
+
+        let mut open = self.open;
+        egui::Window::new("在线验证")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                let running = self.receiver.is_some();
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal(|ui| {
+                        ComboBox::from_id_salt("oracle_method")
+                            .width(70.0)
+                            .selected_text(self.method)
+                            .show_ui(ui, |ui| {
+                                for method in METHODS {
+                                    ui.selectable_value(&mut self.method, method, method);
+                                }
+                            });
+                        ui.add(
+                            TextEdit::singleline(&mut self.url)
+                                .desired_width(ui.available_width())
+                                .hint_text("目标接口，可以包含 {{token}}"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("令牌位置:");
+                        ui.radio_value(&mut self.placement, PlacementKind::Bearer, "Bearer");
+                        ui.radio_value(&mut self.placement, PlacementKind::Header, "请求头");
+                        ui.radio_value(&mut self.placement, PlacementKind::Cookie, "Cookie");
+                        ui.radio_value(&mut self.placement, PlacementKind::Query, "查询参数");
+                    });
+                    if self.placement != PlacementKind::Bearer {
+                        ui.horizontal(|ui| {
+                            ui.label("名称:");
+                            ui.text_edit_singleline(&mut self.param_name);
+                        });
+                    }
+                    if self.method != "GET" {
+                        ui.add(
+                            TextEdit::multiline(&mut self.body)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_rows(2)
+                                .desired_width(ui.available_width())
+                                .hint_text("请求体，可以包含 {{token}}"),
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("接受的状态码:");
+                        ui.add(
+                            TextEdit::singleline(&mut self.accept_status)
+                                .desired_width(80.0)
+                                .hint_text("200,204"),
+                        );
+                        ui.label("响应正则:");
+                        ui.text_edit_singleline(&mut self.accept_regex);
+                    })
+                    .response
+                    .on_hover_text("都不填时，状态码与签名无效的令牌不同即视为接受");
+                    ui.horizontal(|ui| {
+                        ui.label("每秒请求数:");
+                        ui.add(egui::DragValue::new(&mut self.rate).range(0.0..=100.0));
+                        if ui.button("开始验证").clicked() {
+                            self.start(ctx, token, secret, forged_token);
+                        }
+                    });
+                });
+                if running {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("已发送 {} 个令牌", self.verdicts.len()));
+                    });
+                }
+                if let Some(error) = &self.error {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+                ui.separator();
+                egui::Grid::new("oracle_verdicts")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for verdict in &self.verdicts {
+                            ui.label(&verdict.forgery.name)
+                                .on_hover_text(&verdict.forgery.token);
+                            ui.label(verdict.response.status.to_string());
+                            ui.label(format!("{} 字节", verdict.response.body.len()));
+                            if verdict.accepted {
+                                ui.label(RichText::new("接受").color(Color32::RED));
+                            } else {
+                                ui.label(RichText::new("拒绝").color(Color32::GRAY));
+                            }
+                            if ui.small_button("复制").clicked() {
+                                ui.ctx().copy_text(verdict.forgery.token.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.open = open;
+    }
+
+    fn start(&mut self, ctx: &egui::Context, token: &str, secret: Option<&[u8]>, forged: &str) {
+        self.error = None;
+        self.verdicts.clear();
+        let (template, classifier, forgeries) = match self.prepare(token, secret, forged) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+
+        let (sender, receiver) = channel();
+        self.receiver = Some(receiver);
+        let token = token.to_string();
+        let rate = self.rate;
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = Oracle::new(template, classifier, rate, Duration::from_secs(10)).and_then(
+                |mut oracle| {
+                    oracle.probe(&token, &forgeries, |verdict| {
+                        let _ = sender.send(OracleMessage::Verdict(verdict.clone()));
+                        ctx.request_repaint();
+                    })
+                },
+            );
+            let _ = sender.send(OracleMessage::Finished(result.err().map(|e| e.to_string())));
+            ctx.request_repaint();
+        });
+    }
+
+    fn prepare(
+        &self,
+        token: &str,
+        secret: Option<&[u8]>,
+        forged: &str,
+    ) -> Result<(RequestTemplate, Classifier, Vec<Forgery>), String> {
+        let mut status = Vec::new();
+        for code in self.accept_status.split(',').map(str::trim) {
+            if !code.is_empty() {
+                status.push(
+                    code.parse()
+                        .map_err(|_| format!("无效的状态码: {}", code))?,
+                );
+            }
+        }
+        let body = if self.accept_regex.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&self.accept_regex).map_err(|e| format!("无效的正则: {}", e))?)
+        };
+        let name = self.param_name.clone();
+        let placement = match self.placement {
+            PlacementKind::Bearer => TokenPlacement::Bearer,
+            PlacementKind::Header => TokenPlacement::Header {
+                name,
+                prefix: "".to_string(),
+            },
+            PlacementKind::Cookie => TokenPlacement::Cookie { name },
+            PlacementKind::Query => TokenPlacement::Query { name },
+        };
+        let template = RequestTemplate {
+            method: self.method.to_string(),
+            url: self.url.clone(),
+            headers: Vec::new(),
+            body: (self.method != "GET" && !self.body.is_empty()).then(|| self.body.clone()),
+            placement,
+        };
+
+        let mut forgeries =
+            oracle::forgeries(token, None, secret, None).map_err(|e| e.to_string())?;
+        if !forged.is_empty() {
+            forgeries.push(Forgery::new("forge_window", forged.to_string()));
+        }
+        let classifier = Classifier {
+            status,
+            body,
+            length_tolerance: None,
+        };
+        Ok((template, classifier, forgeries))
+    }
+
+    fn handle_messages(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                OracleMessage::Verdict(verdict) => self.verdicts.push(verdict),
+                OracleMessage::Finished(error) => {
+                    self.error = error;
+                    self.receiver = None;
+                    return;
+                }
+            }
+        }
+    }
+}
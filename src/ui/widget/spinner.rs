@@ -7,8 +7,6 @@ use epaint::{Color32, Pos2, Rect, Shape, Stroke, emath::lerp, vec2};
 #[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
 #[derive(Default)]
 pub struct Spinner {
-    /// Uses the style's `interact_size` if `None`.
-    size: Option<f32>,
     color: Option<Color32>,
     speed: Option<f64>,      // 旋转速度
     clockwise: Option<bool>, // 是否顺时针
@@ -20,14 +18,6 @@ impl Spinner {
         Self::default()
     }
 
-    /// Sets the spinner's size. The size sets both the height and width, as the spinner is always
-    /// square. If the size isn't set explicitly, the active style's `interact_size` is used.
-    #[inline]
-    pub fn size(mut self, size: f32) -> Self {
-        self.size = Some(size);
-        self
-    }
-
     /// Sets the spinner's color.
     #[inline]
     pub fn color(mut self, color: impl Into<Color32>) -> Self {
@@ -76,9 +66,7 @@ impl Spinner {
 
 impl Widget for Spinner {
     fn ui(self, ui: &mut Ui) -> Response {
-        let size = self
-            .size
-            .unwrap_or_else(|| ui.style().spacing.interact_size.y);
+        let size = ui.style().spacing.interact_size.y;
         let (rect, response) = ui.allocate_exact_size(vec2(size, size), Sense::hover());
         let speed = self.speed.unwrap_or(1.0);
         let clockwise = self.clockwise.unwrap_or(true);
@@ -0,0 +1,514 @@
+use crate::error::Result;
+use crate::utils::jwt::{
+    Verifier, claim_secs, decode_json_segment, split_token, time_claim_warnings,
+};
+use crate::utils::time::now_secs;
+use base64::Engine;
+use base64::alphabet::URL_SAFE;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use serde_json::{Map, Value, json};
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+
+/// 审计时顺带尝试的常见弱密钥
+pub const COMMON_SECRETS: &[&str] = &[
+    "secret",
+    "Secret",
+    "SECRET",
+    "secretkey",
+    "secret_key",
+    "secret-key",
+    "jwt",
+    "jwtsecret",
+    "jwt_secret",
+    "jwt-secret",
+    "key",
+    "password",
+    "123456",
+    "12345678",
+    "changeme",
+    "admin",
+    "test",
+    "default",
+    "your-256-bit-secret",
+    "your-384-bit-secret",
+    "your-512-bit-secret",
+    "mysecret",
+    "my_secret",
+    "supersecret",
+    "s3cr3t",
+];
+
+/// 超过这个有效期的令牌会被标记为过长
+const LONG_LIFETIME_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// 被视为敏感信息的声明名片段
+const SENSITIVE_CLAIM_NAMES: &[&str] = &[
+    "password", "passwd", "pwd", "secret", "apikey", "api_key", "token", "ssn", "credit",
+];
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        }
+    }
+}
+
+/// 审计发现的一条问题
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// 一个令牌的完整审计结果
+#[derive(Debug, Clone)]
+pub struct AuditReport {
+    pub header: Value,
+    pub payload: Value,
+    pub cracked_secret: Option<String>,
+    pub findings: Vec<Finding>,
+}
+
+/// 对令牌做静态安全审计，`known_secret` 是已经爆破出来的密钥（如果有）
+pub fn audit_token(token: &str, known_secret: Option<&str>) -> Result<AuditReport> {
+    let parts = split_token(token)?;
+    let header = decode_json_segment("header", parts.header)?;
+    let payload = decode_json_segment("payload", parts.payload)?;
+
+    let mut report = AuditReport {
+        header,
+        payload,
+        cracked_secret: None,
+        findings: Vec::new(),
+    };
+
+    for (name, segment) in [
+        ("header", parts.header),
+        ("payload", parts.payload),
+        ("signature", parts.signature),
+    ] {
+        check_canonical_base64(&mut report, name, segment);
+    }
+    check_algorithm(&mut report, token, known_secret);
+    check_key_references(&mut report);
+    check_time_claims(&mut report);
+    check_sensitive_claims(&mut report);
+
+    report
+        .findings
+        .sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    Ok(report)
+}
+
+impl AuditReport {
+    /// 人类可读的文本报告
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let alg = self.header["alg"].as_str().unwrap_or("?");
+        let _ = writeln!(text, "算法: {}", alg);
+        if let Some(secret) = &self.cracked_secret {
+            let _ = writeln!(text, "密钥: {}", secret);
+        }
+        if self.findings.is_empty() {
+            let _ = writeln!(text, "未发现问题");
+        }
+        for finding in &self.findings {
+            let _ = writeln!(
+                text,
+                "[{:<6}] {}: {}",
+                finding.severity.as_str(),
+                finding.code,
+                finding.message
+            );
+        }
+        text
+    }
+
+    /// JSON 格式的报告
+    pub fn to_json(&self) -> Value {
+        let findings: Vec<Value> = self
+            .findings
+            .iter()
+            .map(|finding| {
+                json!({
+                    "severity": finding.severity.as_str(),
+                    "code": finding.code,
+                    "message": finding.message,
+                })
+            })
+            .collect();
+        json!({
+            "header": self.header,
+            "payload": self.payload,
+            "cracked_secret": self.cracked_secret,
+            "findings": findings,
+        })
+    }
+
+    fn add(&mut self, severity: Severity, code: &'static str, message: impl Into<String>) {
+        self.findings.push(Finding {
+            severity,
+            code,
+            message: message.into(),
+        });
+    }
+}
+
+/// 带填充、标准字母表或尾部多余比特的分段，严格的库会拒绝，宽松的库会接受
+fn check_canonical_base64(report: &mut AuditReport, name: &str, segment: &str) {
+    let lenient = GeneralPurpose::new(
+        &URL_SAFE,
+        GeneralPurposeConfig::new()
+            .with_decode_allow_trailing_bits(true)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let normalized = segment.replace('+', "-").replace('/', "_");
+    let Ok(bytes) = lenient.decode(&normalized) else {
+        report.add(
+            Severity::Medium,
+            "invalid_base64",
+            format!("{} 不是合法的 Base64URL", name),
+        );
+        return;
+    };
+    if URL_SAFE_NO_PAD.encode(bytes) != segment {
+        report.add(
+            Severity::Low,
+            "non_canonical_base64",
+            format!("{} 的 Base64URL 编码不规范", name),
+        );
+    }
+}
+
+fn check_algorithm(report: &mut AuditReport, token: &str, known_secret: Option<&str>) {
+    let alg = report.header["alg"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    if alg.eq_ignore_ascii_case("none") {
+        report.add(Severity::High, "alg_none", "alg 为 none，令牌没有签名");
+        return;
+    }
+    if alg.is_empty() {
+        report.add(Severity::Medium, "alg_missing", "Header 中缺少 alg");
+        return;
+    }
+    // HS256/HS384/HS512 都用常见弱密钥试一遍，其他算法无法离线校验
+    let Ok(verifier) = Verifier::new(token) else {
+        return;
+    };
+
+    let cracked = known_secret
+        .into_iter()
+        .chain(COMMON_SECRETS.iter().copied())
+        .find(|secret| verifier.verify(secret.as_bytes()));
+    if let Some(secret) = cracked {
+        report.add(
+            Severity::High,
+            "weak_secret",
+            format!("HMAC 密钥已被破解: {}", secret),
+        );
+        report.cracked_secret = Some(secret.to_string());
+    }
+}
+
+fn check_key_references(report: &mut AuditReport) {
+    for field in ["jku", "x5u"] {
+        if let Some(url) = report.header[field].as_str() {
+            let message = format!(
+                "Header 中存在 {}: {}，服务端可能从该地址获取公钥",
+                field, url
+            );
+            report.add(Severity::Medium, "key_url", message);
+        }
+    }
+    if report.header.get("jwk").is_some() {
+        report.add(Severity::Medium, "embedded_jwk", "Header 中内嵌了 jwk 公钥");
+    }
+    if let Some(kid) = report.header.get("kid") {
+        let kid = kid.as_str().map(str::to_string).unwrap_or(kid.to_string());
+        if kid.contains("..") || kid.contains('/') || kid.contains('\'') || kid.contains('|') {
+            let message = format!("kid 包含路径或注入字符: {}", kid);
+            report.add(Severity::High, "kid_injection", message);
+        } else {
+            let message = format!("Header 中存在 kid: {}，可尝试注入", kid);
+            report.add(Severity::Info, "kid_present", message);
+        }
+    }
+}
+
+fn check_time_claims(report: &mut AuditReport) {
+    let now = now_secs();
+    let exp = claim_secs(&report.payload, "exp");
+    let iat = claim_secs(&report.payload, "iat");
+
+    match exp {
+        None => report.add(Severity::Medium, "exp_missing", "缺少 exp，令牌永不过期"),
+        Some(exp) => {
+            let lifetime = exp.saturating_sub(iat.unwrap_or(now));
+            if lifetime > LONG_LIFETIME_SECS {
+                let message = format!("令牌有效期过长: {} 天", lifetime / 86_400);
+                report.add(Severity::Low, "exp_too_long", message);
+            }
+        }
+    }
+    if iat.is_none() {
+        report.add(Severity::Low, "iat_missing", "缺少 iat");
+    }
+    if report.payload.get("nbf").is_none() {
+        report.add(Severity::Info, "nbf_missing", "缺少 nbf");
+    }
+    for warning in time_claim_warnings(&report.payload) {
+        report.add(Severity::Info, "time_claim", warning.to_string());
+    }
+}
+
+fn check_sensitive_claims(report: &mut AuditReport) {
+    let mut found = Vec::new();
+    if let Value::Object(claims) = &report.payload {
+        collect_sensitive(claims, "", &mut found);
+    }
+    for (code, message) in found {
+        report.add(Severity::Medium, code, message);
+    }
+}
+
+fn collect_sensitive(claims: &Map<String, Value>, prefix: &str, found: &mut Vec<(&str, String)>) {
+    for (name, value) in claims {
+        let path = format!("{}{}", prefix, name);
+        let lower = name.to_lowercase();
+        if SENSITIVE_CLAIM_NAMES.iter().any(|s| lower.contains(s)) {
+            found.push(("sensitive_claim", format!("声明 {} 看起来是敏感数据", path)));
+        }
+        collect_sensitive_value(value, &path, found);
+    }
+}
+
+/// 检查单个值，数组中的元素按 `path[i]` 命名
+fn collect_sensitive_value(value: &Value, path: &str, found: &mut Vec<(&str, String)>) {
+    match value {
+        Value::Object(inner) => collect_sensitive(inner, &format!("{}.", path), found),
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_sensitive_value(item, &format!("{}[{}]", path, index), found);
+            }
+        }
+        Value::String(text) => {
+            if looks_like_email(text) {
+                found.push(("email", format!("声明 {} 包含邮箱地址", path)));
+            }
+            if let Some(ip) = find_internal_ip(text) {
+                found.push(("internal_ip", format!("声明 {} 包含内网地址 {}", path, ip)));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn looks_like_email(text: &str) -> bool {
+    text.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .any(|word| match word.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty() && domain.contains('.') && !domain.ends_with('.')
+            }
+            None => false,
+        })
+}
+
+fn find_internal_ip(text: &str) -> Option<Ipv4Addr> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|word| word.parse::<Ipv4Addr>().ok())
+        .find(|ip| ip.is_private() || ip.is_loopback() || ip.is_link_local())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::forge::forge_alg_none;
+    use crate::utils::jwt::{HmacAlgorithm, encode_segment, sign_hmac};
+
+    const STRONG_SECRET: &[u8] = b"c0rrect-h0rse-battery-stapl3-9f2e";
+
+    fn token(header: Value, payload: Value) -> String {
+        sign_hmac(&header, &payload, HmacAlgorithm::HS256, STRONG_SECRET)
+    }
+
+    /// 有效期一小时、时间声明齐全的 Payload
+    fn clean_payload() -> Value {
+        let now = now_secs();
+        json!({ "sub": "alice", "iat": now, "nbf": now, "exp": now + 3600 })
+    }
+
+    fn codes(token: &str) -> Vec<&'static str> {
+        let mut codes: Vec<_> = audit_token(token, None)
+            .unwrap()
+            .findings
+            .iter()
+            .map(|finding| finding.code)
+            .collect();
+        codes.sort();
+        codes
+    }
+
+    #[test]
+    fn clean_token_has_no_findings() {
+        let report = audit_token(&token(json!({ "alg": "HS256" }), clean_payload()), None).unwrap();
+        assert!(report.findings.is_empty(), "{:?}", report.findings);
+        assert_eq!(report.cracked_secret, None);
+    }
+
+    #[test]
+    fn algorithm_findings() {
+        let none = forge_alg_none(&json!({}), &clean_payload(), "nOnE");
+        assert_eq!(codes(&none), ["alg_none"]);
+        let missing = format!(
+            "{}.{}.sig",
+            encode_segment(&json!({ "typ": "JWT" })),
+            encode_segment(&clean_payload())
+        );
+        assert_eq!(codes(&missing), ["alg_missing"]);
+    }
+
+    #[test]
+    fn weak_secrets_are_cracked_for_every_hmac_algorithm() {
+        for algorithm in [
+            HmacAlgorithm::HS256,
+            HmacAlgorithm::HS384,
+            HmacAlgorithm::HS512,
+        ] {
+            let weak = sign_hmac(&json!({}), &clean_payload(), algorithm, b"changeme");
+            let report = audit_token(&weak, None).unwrap();
+            assert_eq!(report.cracked_secret.as_deref(), Some("changeme"));
+            assert_eq!(report.findings[0].code, "weak_secret");
+        }
+        let known = token(json!({}), clean_payload());
+        let secret = std::str::from_utf8(STRONG_SECRET).unwrap();
+        let report = audit_token(&known, Some(secret)).unwrap();
+        assert_eq!(report.cracked_secret.as_deref(), Some(secret));
+    }
+
+    #[test]
+    fn time_claim_findings() {
+        let now = now_secs();
+        assert_eq!(
+            codes(&token(json!({}), json!({ "iat": now, "nbf": now }))),
+            ["exp_missing"]
+        );
+        assert_eq!(
+            codes(&token(
+                json!({}),
+                json!({ "iat": now, "nbf": now, "exp": now + 365 * 86_400 })
+            )),
+            ["exp_too_long"]
+        );
+        assert_eq!(
+            codes(&token(json!({}), json!({ "nbf": now, "exp": now + 60 }))),
+            ["iat_missing"]
+        );
+        assert_eq!(
+            codes(&token(json!({}), json!({ "iat": now, "exp": now + 60 }))),
+            ["nbf_missing"]
+        );
+        // 小数形式的 exp 也算存在
+        assert_eq!(
+            codes(&token(
+                json!({}),
+                json!({ "iat": now, "nbf": now, "exp": now as f64 + 60.5 })
+            )),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            codes(&token(
+                json!({}),
+                json!({ "iat": now - 120, "nbf": now - 120, "exp": now - 60 })
+            )),
+            ["time_claim"]
+        );
+    }
+
+    #[test]
+    fn key_reference_findings() {
+        let header = json!({
+            "jku": "http://attacker/jwks.json",
+            "x5u": "http://attacker/cert.pem",
+            "jwk": { "kty": "oct" },
+            "kid": "key-1",
+        });
+        assert_eq!(
+            codes(&token(header, clean_payload())),
+            ["embedded_jwk", "key_url", "key_url", "kid_present"]
+        );
+        for kid in ["../../dev/null", "1' OR '1'='1", "key|id"] {
+            assert_eq!(
+                codes(&token(json!({ "kid": kid }), clean_payload())),
+                ["kid_injection"],
+                "{}",
+                kid
+            );
+        }
+    }
+
+    #[test]
+    fn sensitive_claims_are_found_in_nested_values() {
+        let mut payload = clean_payload();
+        payload["profile"] = json!({
+            "contacts": [{ "mail": "alice@example.com" }],
+            "Password_Hash": "x",
+        });
+        payload["hosts"] = json!(["db 10.0.0.5", "8.8.8.8"]);
+        payload["note"] = json!("no@dot");
+        assert_eq!(
+            codes(&token(json!({}), payload)),
+            ["email", "internal_ip", "sensitive_claim"]
+        );
+        let report = audit_token(
+            &token(
+                json!({}),
+                json!({ "a": [{ "b": "c@d.io" }], "iat": 1, "nbf": 1, "exp": 2 }),
+            ),
+            None,
+        )
+        .unwrap();
+        assert!(
+            report
+                .findings
+                .iter()
+                .any(|finding| finding.code == "email" && finding.message.contains("a[0].b"))
+        );
+    }
+
+    #[test]
+    fn non_canonical_base64_is_reported() {
+        // Header 和 Payload 解码时就很严格，只有签名可能不规范
+        let valid = token(json!({}), clean_payload());
+        assert_eq!(codes(&format!("{}=", valid)), ["non_canonical_base64"]);
+        let (signing_input, signature) = valid.rsplit_once('.').unwrap();
+        let standard = signature.replace('-', "+").replace('_', "/");
+        if standard != signature {
+            assert_eq!(
+                codes(&format!("{}.{}", signing_input, standard)),
+                ["non_canonical_base64"]
+            );
+        }
+        assert_eq!(
+            codes(&format!("{}.{}", signing_input, "!!!!")),
+            ["invalid_base64"]
+        );
+    }
+}
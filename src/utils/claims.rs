@@ -0,0 +1,139 @@
+use crate::utils::time::{format_timestamp, now_secs};
+use serde_json::{Value, json};
+
+/// 和时间相关的标准声明
+pub const TIME_CLAIMS: [&str; 3] = ["exp", "iat", "nbf"];
+
+/// 常用的提权模板：按钮上的文字和要合并进Payload的声明
+pub const ESCALATION_TEMPLATES: &[(&str, &str)] = &[
+    ("admin: true", r#"{"admin": true}"#),
+    ("isAdmin: true", r#"{"isAdmin": true}"#),
+    ("role: \"admin\"", r#"{"role": "admin"}"#),
+    ("roles: [\"admin\"]", r#"{"roles": ["admin"]}"#),
+    ("scope: \"admin\"", r#"{"scope": "admin"}"#),
+    ("sub: \"admin\"", r#"{"sub": "admin"}"#),
+];
+
+/// 可以一键执行的声明修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimEdit {
+    /// 在 `exp` 和当前时间中较晚的那个基础上延长
+    ExtendExp(u64),
+    /// 把指定的时间声明设置为当前时间
+    SetNow(&'static str),
+    /// 删除指定声明
+    Remove(&'static str),
+    /// 合并 `ESCALATION_TEMPLATES` 中的一个模板
+    Escalate(usize),
+}
+
+/// 对Payload执行一次修改，Payload不是JSON对象时返回 `false`
+pub fn apply_claim_edit(payload: &mut Value, edit: ClaimEdit) -> bool {
+    let Some(claims) = payload.as_object_mut() else {
+        return false;
+    };
+    match edit {
+        ClaimEdit::ExtendExp(secs) => {
+            let base = claims
+                .get("exp")
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+                .max(now_secs());
+            claims.insert("exp".to_string(), json!(base.saturating_add(secs)));
+        }
+        ClaimEdit::SetNow(name) => {
+            claims.insert(name.to_string(), json!(now_secs()));
+        }
+        ClaimEdit::Remove(name) => {
+            claims.remove(name);
+        }
+        ClaimEdit::Escalate(index) => {
+            let Some((_, template)) = ESCALATION_TEMPLATES.get(index) else {
+                return false;
+            };
+            if let Ok(Value::Object(template)) = serde_json::from_str::<Value>(template) {
+                claims.extend(template);
+            }
+        }
+    }
+    true
+}
+
+/// 以可读形式列出Payload中的时间声明
+pub fn describe_time_claims(payload: &Value) -> Vec<(&'static str, String)> {
+    TIME_CLAIMS
+        .iter()
+        .filter_map(|&name| {
+            let secs = payload.get(name)?.as_u64()?;
+            Some((name, format_timestamp(secs)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_exp() {
+        let mut payload = json!({ "exp": u64::MAX - 1 });
+        assert!(apply_claim_edit(&mut payload, ClaimEdit::ExtendExp(10)));
+        assert_eq!(payload["exp"], u64::MAX);
+
+        // 没有 exp 或者已经过期时从当前时间算起
+        for mut payload in [json!({}), json!({ "exp": 1 })] {
+            let before = now_secs();
+            apply_claim_edit(&mut payload, ClaimEdit::ExtendExp(3600));
+            let exp = payload["exp"].as_u64().unwrap();
+            assert!((before + 3600..=now_secs() + 3600).contains(&exp));
+        }
+
+        let far = now_secs() + 1_000_000;
+        let mut payload = json!({ "exp": far });
+        apply_claim_edit(&mut payload, ClaimEdit::ExtendExp(60));
+        assert_eq!(payload["exp"], far + 60);
+    }
+
+    #[test]
+    fn set_now_and_remove() {
+        let mut payload = json!({ "iat": 1, "exp": 2, "sub": "alice" });
+        let before = now_secs();
+        assert!(apply_claim_edit(&mut payload, ClaimEdit::SetNow("iat")));
+        let iat = payload["iat"].as_u64().unwrap();
+        assert!((before..=now_secs()).contains(&iat));
+        assert!(apply_claim_edit(&mut payload, ClaimEdit::Remove("exp")));
+        assert_eq!(payload, json!({ "iat": iat, "sub": "alice" }));
+        // 删除不存在的声明不算失败
+        assert!(apply_claim_edit(&mut payload, ClaimEdit::Remove("nbf")));
+    }
+
+    #[test]
+    fn escalation_templates() {
+        for (index, (label, _)) in ESCALATION_TEMPLATES.iter().enumerate() {
+            let mut payload = json!({ "sub": "alice" });
+            assert!(apply_claim_edit(&mut payload, ClaimEdit::Escalate(index)));
+            let claim = label.split(':').next().unwrap();
+            assert!(payload.get(claim).is_some(), "{}", label);
+        }
+        let mut payload = json!({ "sub": "alice" });
+        apply_claim_edit(&mut payload, ClaimEdit::Escalate(5));
+        assert_eq!(payload, json!({ "sub": "admin" }));
+        assert!(!apply_claim_edit(
+            &mut payload,
+            ClaimEdit::Escalate(ESCALATION_TEMPLATES.len())
+        ));
+        assert!(!apply_claim_edit(&mut json!([1]), ClaimEdit::Remove("exp")));
+    }
+
+    #[test]
+    fn describe_time_claims_formats_known_claims() {
+        let payload = json!({ "exp": 951_782_400, "iat": 0, "nbf": "soon", "sub": 1 });
+        assert_eq!(
+            describe_time_claims(&payload),
+            [
+                ("exp", "2000-02-29 00:00:00 UTC".to_string()),
+                ("iat", "1970-01-01 00:00:00 UTC".to_string()),
+            ]
+        );
+    }
+}
@@ -1,46 +1,166 @@
-use crossbeam_channel::Receiver;
-use std::sync::Arc;
+use crate::cancel::CancelToken;
+use crate::error::{Error, Result};
+use crate::source::CandidateSource;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+pub const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &str = "0123456789";
+pub const SPECIAL: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// 解析字节字符集，例如 `\x00-\xff`、`a-z\x00`
+///
+/// 支持 `\xHH`、`\\`、`\-` 转义和 `X-Y` 范围，其余只能是 ASCII 字符，重复的字节只保留一个
+pub fn parse_byte_charset(spec: &str) -> Result<Vec<u8>> {
+    let invalid = |reason: &str| Error::InvalidConfig(format!("字节字符集 {} {}", spec, reason));
+    // `None` 表示范围中间的 `-`
+    let mut tokens = Vec::new();
+    let mut chars = spec.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '\\' => match chars.next() {
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    if hex.len() != 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(invalid("中的 \\x 后面需要两位十六进制数"));
+                    }
+                    u8::from_str_radix(&hex, 16).ok()
+                }
+                Some(escaped @ ('\\' | '-')) => Some(escaped as u8),
+                _ => return Err(invalid("中有未知的转义")),
+            },
+            '-' => None,
+            c if c.is_ascii() => Some(c as u8),
+            _ => return Err(invalid("只能包含 ASCII 字符和 \\xHH")),
+        });
+    }
+
+    let mut bytes = Vec::new();
+    let mut seen = [false; 256];
+    let mut push = |byte: u8| {
+        if !seen[byte as usize] {
+            seen[byte as usize] = true;
+            bytes.push(byte);
+        }
+    };
+    let mut i = 0;
+    while i < tokens.len() {
+        match (tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (Some(start), Some(None), Some(&Some(end))) => {
+                if start > end {
+                    return Err(invalid("中的范围起点大于终点"));
+                }
+                (start..=end).for_each(&mut push);
+                i += 3;
+            }
+            (token, _, _) => {
+                push(token.unwrap_or(b'-'));
+                i += 1;
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// 按 NFC 规范化并去掉重复的字符，保留每个字符第一次出现的位置
+///
+/// 例如 `e` + U+0301 会合并为 `é`，避免同一个字形对应多种字节序列
+pub fn normalize_charset(charset: &str) -> String {
+    let mut normalized = String::new();
+    for c in charset.nfc() {
+        if !normalized.contains(c) {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// 字符集中可能导致意外结果的地方：重复字符、规范化后仍单独存在的组合字符、多字节字符
+pub fn charset_warnings(charset: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let nfc: String = charset.nfc().collect();
+    if nfc != charset {
+        warnings.push("字符集已按 NFC 规范化，组合字符序列被合并为单个字符".to_string());
+    }
+    let mut seen = String::new();
+    let mut duplicates = String::new();
+    for c in nfc.chars() {
+        if seen.contains(c) {
+            if !duplicates.contains(c) {
+                duplicates.push(c);
+            }
+        } else {
+            seen.push(c);
+        }
+    }
+    if !duplicates.is_empty() {
+        warnings.push(format!("字符集中有重复的字符 {:?}，已去重", duplicates));
+    }
+    let combining: Vec<String> = seen
+        .chars()
+        .filter(|&c| is_combining_mark(c))
+        .map(|c| format!("U+{:04X}", c as u32))
+        .collect();
+    if !combining.is_empty() {
+        warnings.push(format!(
+            "字符集中有组合字符 {}，会与前一个字符显示在一起",
+            combining.join(" ")
+        ));
+    }
+    if !seen.is_ascii() {
+        warnings.push("字符集中有多字节字符，按字符计算的长度小于密钥的字节数".to_string());
+    }
+    warnings
+}
 
 pub struct CombinationGenerator {
-    charset: Vec<char>,
+    /// 每个符号编码后的字节，普通字符集为 UTF-8，字节字符集为单个字节
+    symbols: Vec<Vec<u8>>,
     current_length: usize,
     min_length: usize,
     max_length: usize,
     indices: Vec<usize>,
     is_new_length: bool,
-    stop_rx: Option<Arc<Receiver<()>>>,
+    /// `seek` 之后下一次直接产出当前的 indices
+    pending: bool,
+    cancel: Option<CancelToken>,
 }
 
 impl CombinationGenerator {
-    pub fn new(min_length: usize, max_length: usize, stop_rx: Option<Arc<Receiver<()>>>) -> Self {
-        let charset: Vec<char> = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-            .chars()
-            .collect();
-        Self {
-            charset,
-            current_length: min_length,
-            min_length,
-            max_length,
-            indices: Vec::new(),
-            is_new_length: true,
-            stop_rx,
-        }
+    pub fn new(min_length: usize, max_length: usize, cancel: Option<CancelToken>) -> Self {
+        let charset = format!("{}{}{}", DIGITS, LOWERCASE, UPPERCASE);
+        Self::new_with_charset(min_length, max_length, &charset, cancel)
     }
+    /// 字符集会先经过 [`normalize_charset`]，重复的字符只算一次
     pub fn new_with_charset(
         min_length: usize,
         max_length: usize,
         charset: &str,
-        stop_rx: Option<Arc<Receiver<()>>>,
+        cancel: Option<CancelToken>,
+    ) -> Self {
+        let symbols = normalize_charset(charset)
+            .chars()
+            .map(|c| c.to_string().into_bytes())
+            .collect();
+        Self::new_with_symbols(min_length, max_length, symbols, cancel)
+    }
+    /// 每个候选由 `symbols` 中的符号拼接而成，例如 [`parse_byte_charset`] 得到的单个字节
+    pub fn new_with_symbols(
+        min_length: usize,
+        max_length: usize,
+        symbols: Vec<Vec<u8>>,
+        cancel: Option<CancelToken>,
     ) -> Self {
-        let charset: Vec<char> = charset.chars().collect();
         Self {
-            charset,
+            symbols,
             current_length: min_length,
             min_length,
             max_length,
             indices: Vec::new(),
             is_new_length: true,
-            stop_rx,
+            pending: false,
+            cancel,
         }
     }
     pub fn new_with_options(
@@ -50,77 +170,286 @@ impl CombinationGenerator {
         use_uppercase: bool,
         use_digits: bool,
         use_special: bool,
-        stop_rx: Option<Arc<Receiver<()>>>,
+        cancel: Option<CancelToken>,
     ) -> Self {
         let mut charset = String::new();
         if use_lowercase {
-            charset.push_str("abcdefghijklmnopqrstuvwxyz");
+            charset.push_str(LOWERCASE);
         }
         if use_uppercase {
-            charset.push_str("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+            charset.push_str(UPPERCASE);
         }
         if use_digits {
-            charset.push_str("0123456789");
+            charset.push_str(DIGITS);
         }
         if use_special {
-            charset.push_str("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~");
+            charset.push_str(SPECIAL);
         }
-        let charset: Vec<char> = charset.chars().collect();
-        Self {
-            charset,
-            current_length: min_length,
-            min_length,
-            max_length,
-            indices: Vec::new(),
-            is_new_length: true,
-            stop_rx,
+        Self::new_with_charset(min_length, max_length, &charset, cancel)
+    }
+
+    /// 生成的密钥长度范围
+    pub fn length_range(&self) -> std::ops::RangeInclusive<usize> {
+        self.min_length..=self.max_length
+    }
+
+    /// 移动到下一个组合，全部生成完毕或被取消时返回 `false`
+    fn advance(&mut self) -> bool {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            self.current_length = self.max_length + 1;
+            return false;
+        }
+        if self.symbols.is_empty() || self.current_length > self.max_length {
+            return false;
+        }
+        if self.pending {
+            self.pending = false;
+            return true;
+        }
+
+        if self.is_new_length {
+            self.indices = vec![0; self.current_length];
+            self.is_new_length = false;
+            return true;
         }
+
+        for i in (0..self.current_length).rev() {
+            self.indices[i] += 1;
+            if self.indices[i] < self.symbols.len() {
+                return true;
+            }
+            self.indices[i] = 0;
+        }
+
+        // 当前长度已经遍历完，进入下一个长度
+        self.current_length += 1;
+        self.indices = vec![0; self.current_length];
+        self.current_length <= self.max_length
     }
 }
 
+/// 逐个产出候选的字节，字节字符集生成的候选不一定是合法的 UTF-8
 impl Iterator for CombinationGenerator {
-    type Item = String;
+    type Item = Vec<u8>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(rx) = &self.stop_rx {
-            if rx.try_recv().is_ok() {
-                return None;
-            }
+        let mut candidate = Vec::new();
+        self.next_candidate(&mut candidate).then_some(candidate)
+    }
+}
+
+impl CandidateSource for CombinationGenerator {
+    fn keyspace(&self) -> Option<u128> {
+        let base = self.symbols.len() as u128;
+        (self.min_length..=self.max_length).try_fold(0u128, |total, length| {
+            total.checked_add(base.checked_pow(length as u32)?)
+        })
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.advance() {
+            return false;
         }
+        buf.clear();
+        for &i in &self.indices {
+            buf.extend_from_slice(&self.symbols[i]);
+        }
+        true
+    }
 
-        if self.current_length > self.max_length {
-            return None;
+    fn seek(&mut self, mut position: u128) -> bool {
+        let base = self.symbols.len() as u128;
+        for length in self.min_length..=self.max_length {
+            let Some(count) = base.checked_pow(length as u32) else {
+                return false;
+            };
+            if position < count {
+                self.current_length = length;
+                self.indices = vec![0; length];
+                for index in self.indices.iter_mut().rev() {
+                    *index = (position % base) as usize;
+                    position /= base;
+                }
+                self.is_new_length = false;
+                self.pending = true;
+                return true;
+            }
+            position -= count;
         }
+        self.current_length = self.max_length + 1;
+        true
+    }
 
-        if self.is_new_length {
-            self.indices = vec![0; self.current_length];
-            self.is_new_length = false;
-        } else {
-            let mut i = self.current_length - 1;
-            loop {
-                self.indices[i] += 1;
+    fn describe(&self) -> String {
+        format!(
+            "暴力破解 {}-{} 位，字符集 {} 个字符",
+            self.min_length,
+            self.max_length,
+            self.symbols.len()
+        )
+    }
+}
 
-                if self.indices[i] < self.charset.len() {
-                    break;
-                }
+/// 按字节数而不是字符数限制长度的暴力破解，适合包含多字节字符的字符集
+///
+/// 按字节数从短到长产出，同一字节数下按字符集中的顺序排列
+pub struct ByteLengthGenerator {
+    symbols: Vec<Vec<u8>>,
+    /// `counts[l]` 为恰好 l 个字节的组合数，溢出时为 `None`
+    counts: Vec<Option<u128>>,
+    min_length: usize,
+    max_length: usize,
+    position: u128,
+}
+
+impl ByteLengthGenerator {
+    pub fn new(min_length: usize, max_length: usize, mut symbols: Vec<Vec<u8>>) -> Self {
+        symbols.retain(|symbol| !symbol.is_empty());
+        let mut counts = vec![Some(1u128); max_length + 1];
+        for length in 1..=max_length {
+            counts[length] = symbols
+                .iter()
+                .filter(|symbol| symbol.len() <= length)
+                .try_fold(0u128, |total, symbol| {
+                    total.checked_add(counts[length - symbol.len()]?)
+                });
+        }
+        Self {
+            symbols,
+            counts,
+            min_length,
+            max_length,
+            position: 0,
+        }
+    }
 
-                self.indices[i] = 0;
+    /// 把第 `position` 个候选写入 `buf`，超出范围时返回 `false`
+    fn write(&self, mut position: u128, buf: &mut Vec<u8>) -> bool {
+        for length in self.min_length..=self.max_length {
+            let Some(count) = self.counts[length] else {
+                return false;
+            };
+            if position < count {
+                self.write_length(length, position, buf);
+                return true;
+            }
+            position -= count;
+        }
+        false
+    }
 
-                if i == 0 {
-                    self.current_length += 1;
-                    self.is_new_length = true;
+    fn write_length(&self, mut length: usize, mut position: u128, buf: &mut Vec<u8>) {
+        buf.clear();
+        while length > 0 {
+            for symbol in &self.symbols {
+                if symbol.len() > length {
+                    continue;
+                }
+                let rest = self.counts[length - symbol.len()].unwrap_or(0);
+                if position < rest {
+                    buf.extend_from_slice(symbol);
+                    length -= symbol.len();
                     break;
                 }
-
-                i -= 1;
+                position -= rest;
             }
         }
+    }
+}
 
-        if self.current_length > self.max_length {
-            return None;
+impl CandidateSource for ByteLengthGenerator {
+    fn keyspace(&self) -> Option<u128> {
+        (self.min_length..=self.max_length).try_fold(0u128, |total, length| {
+            total.checked_add(self.counts[length]?)
+        })
+    }
+
+    fn next_candidate(&mut self, buf: &mut Vec<u8>) -> bool {
+        if !self.write(self.position, buf) {
+            return false;
         }
+        self.position += 1;
+        true
+    }
+
+    fn seek(&mut self, position: u128) -> bool {
+        if self.keyspace().is_none() {
+            return false;
+        }
+        self.position = position;
+        true
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "暴力破解 {}-{} 字节，字符集 {} 个字符",
+            self.min_length,
+            self.max_length,
+            self.symbols.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::check_seek;
+
+    #[test]
+    fn parse_byte_charset_ranges_and_escapes() {
+        assert_eq!(parse_byte_charset("a-c").unwrap(), b"abc");
+        assert_eq!(parse_byte_charset(r"\x00-\x02x").unwrap(), [0, 1, 2, b'x']);
+        assert_eq!(parse_byte_charset(r"\x00-\xff").unwrap().len(), 256);
+        assert_eq!(parse_byte_charset(r"a\-c").unwrap(), b"a-c");
+        assert_eq!(parse_byte_charset(r"\\-").unwrap(), b"\\-");
+        assert_eq!(parse_byte_charset("aba").unwrap(), b"ab");
+        assert!(parse_byte_charset("c-a").is_err());
+        assert!(parse_byte_charset(r"\x+f").is_err());
+        assert!(parse_byte_charset(r"\xf").is_err());
+        assert!(parse_byte_charset(r"\q").is_err());
+        assert!(parse_byte_charset("中").is_err());
+    }
+
+    #[test]
+    fn seek_matches_iteration() {
+        let symbols = || vec![vec![0x00], vec![0xff], b"a".to_vec()];
+        let all = check_seek(|| CombinationGenerator::new_with_symbols(1, 3, symbols(), None));
+        assert_eq!(all.len(), 3 + 9 + 27);
+        assert_eq!(all[0], [0x00]);
+        assert_eq!(all[3], [0x00, 0x00]);
+        assert_eq!(all[38], b"aaa");
+    }
+
+    #[test]
+    fn normalize_charset_merges_and_dedupes() {
+        assert_eq!(normalize_charset("abca"), "abc");
+        assert_eq!(normalize_charset("e\u{301}é"), "é");
+        let warnings = charset_warnings("aae\u{301}");
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(charset_warnings("abc").is_empty());
+        let generator = CombinationGenerator::new_with_charset(1, 1, "aa", None);
+        assert_eq!(generator.keyspace(), Some(1));
+    }
+
+    #[test]
+    fn byte_length_seek_matches_iteration() {
+        let symbols = || vec![b"a".to_vec(), b"b".to_vec(), "中".as_bytes().to_vec()];
+        let all = check_seek(|| ByteLengthGenerator::new(1, 4, symbols()));
+        assert!(
+            all.iter()
+                .all(|candidate| (1..=4).contains(&candidate.len()))
+        );
+        // 4 字节：16 个只含 a/b 的组合，加上 a/b 与“中”拼接的 4 种
+        assert_eq!(all.iter().filter(|c| c.len() == 4).count(), 20);
+        let mut unique = all.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), all.len());
+    }
 
-        let combination: String = self.indices.iter().map(|&i| self.charset[i]).collect();
-        Some(combination)
+    #[test]
+    fn iterator_yields_raw_bytes() {
+        let generator = CombinationGenerator::new_with_symbols(2, 2, vec![vec![0xff]], None);
+        assert_eq!(generator.collect::<Vec<_>>(), [vec![0xff, 0xff]]);
     }
 }
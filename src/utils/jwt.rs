@@ -1,48 +1,397 @@
+use crate::error::{Error, Result};
+use crate::utils::time::now_secs;
 use base64::Engine;
+use base64::alphabet;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
 use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, from_slice};
-use sha2::Sha256;
+use sha2::{Sha256, Sha384, Sha512};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fmt;
 
-/// 解码JWT字符串并更新UI中的Header和Payload
-/// 参数: main_window - 对MainWindow的可变引用
+/// 按 `.` 拆分后的JWT三个分段
+pub struct JwtParts<'a> {
+    pub header: &'a str,
+    pub payload: &'a str,
+    pub signature: &'a str,
+}
 
-pub fn verify_jwt_hs256_token(token: &str, secret_key: &str) -> Option<HashMap<String, Value>> {
-    let parts: Vec<&str> = token.split('.').collect();
+/// 把JWT拆分为Header、Payload和签名三段
+pub fn split_token(token: &str) -> Result<JwtParts<'_>> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
     if parts.len() != 3 {
-        return None;
+        return Err(Error::MalformedToken { parts: parts.len() });
     }
-    let encoded_header = parts[0];
-    let encoded_payload = parts[1];
-    let encoded_signature = parts[2];
-    let signing_input = format!("{}.{}", encoded_header, encoded_payload);
+    Ok(JwtParts {
+        header: parts[0],
+        payload: parts[1],
+        signature: parts[2],
+    })
+}
 
-    let mut mac =
-        Hmac::<Sha256>::new_from_slice(secret_key.as_bytes()).expect("HMAC-SHA256 实例创建失败");
-    mac.update(signing_input.as_bytes());
+/// 解码一个Base64URL分段，同时尝试解析为JSON，`name` 用于错误信息
+pub fn decode_segment(name: &'static str, segment: &str) -> Result<(Vec<u8>, Option<Value>)> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|source| Error::Base64 {
+            segment: name,
+            source,
+        })?;
+    let json = from_slice::<Value>(&bytes).ok();
+    Ok((bytes, json))
+}
+
+/// 解码一个必须是JSON的分段
+pub fn decode_json_segment(name: &'static str, segment: &str) -> Result<Value> {
+    let bytes = decode_segment(name, segment)?.0;
+    from_slice(&bytes).map_err(|source| Error::Json {
+        segment: name,
+        source,
+    })
+}
+
+/// 解码候选时有没有填充都可以
+const LENIENT: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT);
+const BASE64URL_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT);
 
-    let expected_signature_bytes = mac.finalize().into_bytes();
+/// 候选密钥的解读方式，有些框架会把配置的密钥先按 Base64 解码再作为HMAC密钥
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEncoding {
+    /// 候选本身就是密钥
+    #[default]
+    Raw,
+    Base64,
+    Base64url,
+    Hex,
+}
 
-    let expected_signature = URL_SAFE_NO_PAD.encode(expected_signature_bytes);
-    if expected_signature != encoded_signature {
-        return None;
+impl KeyEncoding {
+    pub const ALL: [KeyEncoding; 4] = [
+        KeyEncoding::Raw,
+        KeyEncoding::Base64,
+        KeyEncoding::Base64url,
+        KeyEncoding::Hex,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyEncoding::Raw => "raw",
+            KeyEncoding::Base64 => "base64",
+            KeyEncoding::Base64url => "base64url",
+            KeyEncoding::Hex => "hex",
+        }
     }
 
-    let decoded_payload_bytes = URL_SAFE_NO_PAD.decode(encoded_payload).ok()?;
-    let payload: HashMap<String, Value> = from_slice(&decoded_payload_bytes).ok()?;
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|encoding| encoding.name() == name)
+    }
 
-    if let Some(exp_value) = payload.get("exp") {
-        if let Some(exp) = exp_value.as_u64() {
-            let current_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            if exp < current_time {
-                return None;
+    /// 按这种方式解读候选得到的HMAC密钥，候选不是合法的编码时返回 `None`
+    pub fn decode(self, candidate: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            KeyEncoding::Raw => Some(candidate.to_vec()),
+            KeyEncoding::Base64 => BASE64_LENIENT.decode(candidate).ok(),
+            KeyEncoding::Base64url => BASE64URL_LENIENT.decode(candidate).ok(),
+            KeyEncoding::Hex => {
+                // from_str_radix 会接受 `+f` 这样带符号的写法
+                if !candidate.len().is_multiple_of(2)
+                    || !candidate.iter().all(u8::is_ascii_hexdigit)
+                {
+                    return None;
+                }
+                candidate
+                    .chunks(2)
+                    .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                    .collect()
             }
         }
     }
-    Some(payload)
+}
+
+/// 支持爆破的HMAC算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlgorithm {
+    HS256,
+    HS384,
+    HS512,
+}
+
+impl HmacAlgorithm {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "HS256" => Some(HmacAlgorithm::HS256),
+            "HS384" => Some(HmacAlgorithm::HS384),
+            "HS512" => Some(HmacAlgorithm::HS512),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HmacAlgorithm::HS256 => "HS256",
+            HmacAlgorithm::HS384 => "HS384",
+            HmacAlgorithm::HS512 => "HS512",
+        }
+    }
+
+    /// 计算 `data` 的HMAC
+    pub fn mac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            HmacAlgorithm::HS256 => mac_with::<Hmac<Sha256>>(key, data),
+            HmacAlgorithm::HS384 => mac_with::<Hmac<Sha384>>(key, data),
+            HmacAlgorithm::HS512 => mac_with::<Hmac<Sha512>>(key, data),
+        }
+    }
+}
+
+fn mac_with<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC 接受任意长度的密钥
+    let Ok(mut mac) = <M as KeyInit>::new_from_slice(key) else {
+        return Vec::new();
+    };
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 用HMAC密钥签发令牌，Header中的 `alg` 会被改写为 `algorithm`
+pub fn sign_hmac(header: &Value, payload: &Value, algorithm: HmacAlgorithm, key: &[u8]) -> String {
+    let mut header = header.as_object().cloned().unwrap_or_default();
+    header.insert("alg".to_string(), Value::from(algorithm.name()));
+    let signing_input = format!(
+        "{}.{}",
+        encode_segment(&Value::Object(header)),
+        encode_segment(payload)
+    );
+    let signature = algorithm.mac(key, signing_input.as_bytes());
+    format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// 预先解析好的令牌，爆破时每个候选密钥只需要计算一次HMAC
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    algorithm: HmacAlgorithm,
+    signing_input: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Verifier {
+    /// 解析令牌，算法取自Header中的 `alg`
+    pub fn new(token: &str) -> Result<Self> {
+        let parts = split_token(token)?;
+        let header = decode_json_segment("header", parts.header)?;
+        let alg = header
+            .get("alg")
+            .and_then(Value::as_str)
+            .ok_or(Error::MissingAlgorithm)?;
+        let algorithm = HmacAlgorithm::from_name(alg)
+            .ok_or_else(|| Error::UnsupportedAlgorithm(alg.to_string()))?;
+        let (signature, _) = decode_segment("signature", parts.signature)?;
+        Ok(Self {
+            algorithm,
+            signing_input: format!("{}.{}", parts.header, parts.payload).into_bytes(),
+            signature,
+        })
+    }
+
+    pub fn algorithm(&self) -> HmacAlgorithm {
+        self.algorithm
+    }
+
+    /// 依次按 `encodings` 解读候选，返回能生成令牌中签名的那一种，`encodings` 为空时按原样校验
+    pub fn verify_encoded(
+        &self,
+        candidate: &[u8],
+        encodings: &[KeyEncoding],
+    ) -> Option<KeyEncoding> {
+        if encodings.is_empty() {
+            return self.verify(candidate).then_some(KeyEncoding::Raw);
+        }
+        encodings.iter().copied().find(|encoding| match encoding {
+            KeyEncoding::Raw => self.verify(candidate),
+            _ => encoding
+                .decode(candidate)
+                .is_some_and(|key| self.verify(&key)),
+        })
+    }
+
+    /// 校验候选密钥是否能生成令牌中的签名
+    pub fn verify(&self, key: &[u8]) -> bool {
+        match self.algorithm {
+            HmacAlgorithm::HS256 => self.verify_with::<Hmac<Sha256>>(key),
+            HmacAlgorithm::HS384 => self.verify_with::<Hmac<Sha384>>(key),
+            HmacAlgorithm::HS512 => self.verify_with::<Hmac<Sha512>>(key),
+        }
+    }
+
+    fn verify_with<M: Mac + KeyInit>(&self, key: &[u8]) -> bool {
+        let Ok(mut mac) = <M as KeyInit>::new_from_slice(key) else {
+            return false;
+        };
+        mac.update(&self.signing_input);
+        mac.verify_slice(&self.signature).is_ok()
+    }
+}
+
+/// 使用HS256校验JWT签名，密钥正确时返回解码后的Payload，不检查过期时间
+pub fn verify_jwt_hs256_token(
+    token: &str,
+    secret_key: impl AsRef<[u8]>,
+) -> Result<Option<HashMap<String, Value>>> {
+    let parts = split_token(token)?;
+    let (signature, _) = decode_segment("signature", parts.signature)?;
+    let signing_input = format!("{}.{}", parts.header, parts.payload);
+
+    // HMAC 接受任意长度的密钥，这里不会失败
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret_key.as_ref()) else {
+        return Ok(None);
+    };
+    mac.update(signing_input.as_bytes());
+    if mac.verify_slice(&signature).is_err() {
+        return Ok(None);
+    }
+
+    // 签名正确就返回，过期等声明问题交给 time_claim_warnings 单独提示
+    let (payload, _) = decode_segment("payload", parts.payload)?;
+    Ok(Some(
+        from_slice::<HashMap<String, Value>>(&payload).unwrap_or_default(),
+    ))
+}
+
+/// 时间相关声明的提示信息，不影响密钥是否正确
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimWarning {
+    /// `exp` 已经过去
+    Expired { exp: u64 },
+    /// `nbf` 还没到
+    NotYetValid { nbf: u64 },
+    /// `iat` 在未来
+    IssuedInFuture { iat: u64 },
+}
+
+impl fmt::Display for ClaimWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaimWarning::Expired { exp } => write!(f, "令牌已过期 (exp: {})", exp),
+            ClaimWarning::NotYetValid { nbf } => write!(f, "令牌尚未生效 (nbf: {})", nbf),
+            ClaimWarning::IssuedInFuture { iat } => write!(f, "签发时间在未来 (iat: {})", iat),
+        }
+    }
+}
+
+/// 时间声明的秒数，小数按整数秒处理
+pub(crate) fn claim_secs(payload: &Value, name: &str) -> Option<u64> {
+    let value = payload.get(name)?;
+    value.as_u64().or_else(|| value.as_f64().map(|v| v as u64))
+}
+
+/// 检查Payload中的 `exp`、`nbf` 和 `iat`，返回需要提示的问题
+pub fn time_claim_warnings(payload: &Value) -> Vec<ClaimWarning> {
+    let now = now_secs();
+    let claim = |name: &str| claim_secs(payload, name);
+
+    let mut warnings = Vec::new();
+    if let Some(exp) = claim("exp")
+        && exp < now
+    {
+        warnings.push(ClaimWarning::Expired { exp });
+    }
+    if let Some(nbf) = claim("nbf")
+        && nbf > now
+    {
+        warnings.push(ClaimWarning::NotYetValid { nbf });
+    }
+    if let Some(iat) = claim("iat")
+        && iat > now
+    {
+        warnings.push(ClaimWarning::IssuedInFuture { iat });
+    }
+    warnings
+}
+
+/// 把JSON序列化并编码为JWT的一个Base64URL分段
+pub fn encode_segment(value: &Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_encoding_decode() {
+        assert_eq!(KeyEncoding::Raw.decode(b"abc"), Some(b"abc".to_vec()));
+        // 有没有填充都可以
+        assert_eq!(KeyEncoding::Base64.decode(b"/w=="), Some(vec![0xff]));
+        assert_eq!(KeyEncoding::Base64.decode(b"/w"), Some(vec![0xff]));
+        assert_eq!(KeyEncoding::Base64url.decode(b"_w"), Some(vec![0xff]));
+        assert_eq!(KeyEncoding::Base64url.decode(b"/w"), None);
+        assert_eq!(KeyEncoding::Hex.decode(b"00fF"), Some(vec![0x00, 0xff]));
+        assert_eq!(KeyEncoding::Hex.decode(b"abc"), None);
+        assert_eq!(KeyEncoding::Hex.decode(b"zz"), None);
+        assert_eq!(KeyEncoding::Hex.decode(b"+f"), None);
+        for encoding in KeyEncoding::ALL {
+            assert_eq!(KeyEncoding::from_name(encoding.name()), Some(encoding));
+        }
+    }
+
+    #[test]
+    fn verify_encoded_reports_the_matching_encoding() {
+        let token = sign_hmac(
+            &serde_json::json!({ "typ": "JWT" }),
+            &serde_json::json!({}),
+            HmacAlgorithm::HS384,
+            &[0xde, 0xad],
+        );
+        let verifier = Verifier::new(&token).unwrap();
+        assert_eq!(verifier.algorithm(), HmacAlgorithm::HS384);
+        assert_eq!(
+            verifier.verify_encoded(b"dead", &KeyEncoding::ALL),
+            Some(KeyEncoding::Hex)
+        );
+        assert_eq!(
+            verifier.verify_encoded(b"3q0=", &KeyEncoding::ALL),
+            Some(KeyEncoding::Base64)
+        );
+        assert_eq!(verifier.verify_encoded(b"dead", &[]), None);
+    }
+
+    #[test]
+    fn expired_token_with_the_right_key_is_found() {
+        let now = now_secs();
+        let token = sign_hmac(
+            &serde_json::json!({ "typ": "JWT" }),
+            &serde_json::json!({ "sub": "alice", "iat": now - 7200, "exp": now - 3600 }),
+            HmacAlgorithm::HS256,
+            b"secret",
+        );
+        let payload = verify_jwt_hs256_token(&token, "secret").unwrap().unwrap();
+        assert_eq!(payload["sub"], "alice");
+        assert_eq!(verify_jwt_hs256_token(&token, "wrong").unwrap(), None);
+        assert!(Verifier::new(&token).unwrap().verify(b"secret"));
+    }
+
+    #[test]
+    fn time_claim_warnings_report_each_problem() {
+        let now = now_secs();
+        let payload =
+            serde_json::json!({ "exp": now - 60, "nbf": now + 60, "iat": now as f64 + 60.5 });
+        assert_eq!(
+            time_claim_warnings(&payload),
+            [
+                ClaimWarning::Expired { exp: now - 60 },
+                ClaimWarning::NotYetValid { nbf: now + 60 },
+                ClaimWarning::IssuedInFuture { iat: now + 60 },
+            ]
+        );
+        let valid = serde_json::json!({ "exp": now + 60, "nbf": now, "iat": now });
+        assert!(time_claim_warnings(&valid).is_empty());
+        assert!(time_claim_warnings(&serde_json::json!({})).is_empty());
+    }
 }
@@ -1,2 +1,6 @@
+pub mod audit;
+pub mod claims;
 pub mod combination_generator;
+pub mod forge;
 pub mod jwt;
+pub mod time;
@@ -0,0 +1,233 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SECS_PER_DAY: u64 = 24 * 60 * 60;
+pub const SECS_PER_YEAR: u64 = 365 * SECS_PER_DAY;
+
+/// 当前的Unix时间戳（秒）
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// 把Unix时间戳格式化为 `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / SECS_PER_DAY) as i64;
+    let rest = secs % SECS_PER_DAY;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+/// 把一段时长格式化为最大的两个单位，例如 `3天 4小时`、`12.5秒`
+pub fn format_duration(secs: f64) -> String {
+    if !secs.is_finite() {
+        return "∞".to_string();
+    }
+    if secs < 60.0 {
+        return format!("{:.1}秒", secs);
+    }
+    let total = secs as u64;
+    let units = [
+        (SECS_PER_YEAR, "年"),
+        (SECS_PER_DAY, "天"),
+        (3600, "小时"),
+        (60, "分"),
+        (1, "秒"),
+    ];
+    let mut parts = Vec::new();
+    let mut rest = total;
+    for (size, name) in units {
+        if rest >= size || !parts.is_empty() {
+            parts.push(format!("{}{}", rest / size, name));
+            rest %= size;
+        }
+        if parts.len() == 2 {
+            break;
+        }
+    }
+    parts.join(" ")
+}
+
+/// 解析 `90`、`30s`、`10m`、`1h30m`、`2d` 这样的时长，纯数字按秒计算
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Ok(secs) = text.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => SECS_PER_DAY,
+            _ => return None,
+        };
+        total = total.checked_add(number.parse::<u64>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+    if !number.is_empty() || text.is_empty() {
+        return None;
+    }
+    Some(Duration::from_secs(total))
+}
+
+/// 把时长写成 [`parse_duration`] 能读回的形式，例如 `1h30m`，不是整秒时直接写秒数
+pub fn duration_to_string(duration: Duration) -> String {
+    if duration.subsec_nanos() != 0 || duration.is_zero() {
+        return duration.as_secs_f64().to_string();
+    }
+    let mut rest = duration.as_secs();
+    let mut text = String::new();
+    for (size, unit) in [(SECS_PER_DAY, 'd'), (3600, 'h'), (60, 'm'), (1, 's')] {
+        if rest >= size {
+            text.push_str(&format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    text
+}
+
+/// 供 `#[serde(with = ...)]` 使用，把 `Option<Duration>` 读写为 `"10m"` 这样的字符串
+pub mod option_duration {
+    use super::{duration_to_string, parse_duration};
+    use serde::{Deserialize, Deserializer, Serializer, de};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_str(&duration_to_string(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => parse_duration(&text)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("无效的时长: {}", text))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// 1970-01-01 起的天数转换为公历年月日（Howard Hinnant 的算法）
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// 公历年月日转换为 1970-01-01 起的天数
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_868_799), "2000-02-29 23:59:59 UTC");
+        assert_eq!(format_timestamp(4_107_542_400), "2100-03-01 00:00:00 UTC");
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1900, 3, 1), -25_508);
+        assert_eq!(days_from_civil(1600, 2, 29), -135_081);
+    }
+
+    #[test]
+    fn civil_round_trip() {
+        for days in (-800_000..800_000).step_by(37) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days, "{}", days);
+        }
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration(" 1.5 "), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(
+            parse_duration("2d"),
+            Some(Duration::from_secs(2 * SECS_PER_DAY))
+        );
+        for invalid in [
+            "",
+            "10x",
+            "m",
+            "1h30",
+            "1.5h",
+            "-5",
+            "99999999999999999999d",
+        ] {
+            assert_eq!(parse_duration(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn duration_strings_round_trip() {
+        for secs in [1, 59, 60, 3600, 5400, 90_061, 3 * SECS_PER_DAY] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(
+                parse_duration(&duration_to_string(duration)),
+                Some(duration)
+            );
+        }
+        assert_eq!(duration_to_string(Duration::from_secs(5400)), "1h30m");
+        for millis in [0, 250, 1500, 90_001] {
+            let duration = Duration::from_millis(millis);
+            assert_eq!(
+                parse_duration(&duration_to_string(duration)),
+                Some(duration)
+            );
+        }
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(12.5), "12.5秒");
+        assert_eq!(format_duration(3600.0), "1小时 0分");
+        assert_eq!(format_duration(90_061.0), "1天 1小时");
+        assert_eq!(format_duration(2.0 * SECS_PER_YEAR as f64 + 5.0), "2年 0天");
+        assert_eq!(format_duration(f64::INFINITY), "∞");
+    }
+}
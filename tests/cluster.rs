@@ -0,0 +1,88 @@
+use jwt_cracker::cluster::{self, ClusterEvent, Coordinator, Message};
+use jwt_cracker::utils::jwt::{HmacAlgorithm, sign_hmac};
+use jwt_cracker::{AttackConfig, AttackMode, CancelToken, RunOptions};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::thread;
+
+#[test]
+fn workers_find_the_key_and_requeue_abandoned_units() {
+    let token = sign_hmac(
+        &json!({ "typ": "JWT" }),
+        &json!({ "sub": "test" }),
+        HmacAlgorithm::HS256,
+        b"9999",
+    );
+    let attack = AttackConfig {
+        stages: vec![AttackMode::Mask {
+            mask: "?d?d?d?d".to_string(),
+            custom_charsets: Vec::new(),
+        }],
+        ..AttackConfig::default()
+    };
+    let coordinator = Coordinator::bind("127.0.0.1:0", &token, attack, 500).unwrap();
+    let addr = coordinator.local_addr().unwrap().to_string();
+    let cancel = CancelToken::new();
+    let events = Mutex::new(Vec::new());
+
+    let found = thread::scope(|scope| {
+        let running = scope
+            .spawn(|| coordinator.run(&cancel, |event| events.lock().unwrap().push(event.clone())));
+
+        // 领到一个单元后就断开的工作端
+        let stream = TcpStream::connect(&addr).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut hello = serde_json::to_vec(&Message::Hello { threads: 1 }).unwrap();
+        hello.push(b'\n');
+        (&stream).write_all(&hello).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(matches!(
+            serde_json::from_str(&line).unwrap(),
+            Message::Job { .. }
+        ));
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        let Message::Unit { id: abandoned, .. } = serde_json::from_str(&line).unwrap() else {
+            panic!("应该收到单元: {}", line);
+        };
+        drop(reader);
+        drop(stream);
+
+        let workers: Vec<_> = (0..3)
+            .map(|_| {
+                let addr = addr.clone();
+                scope.spawn(move || {
+                    let options = RunOptions {
+                        threads: Some(1),
+                        ..RunOptions::default()
+                    };
+                    cluster::run_worker(&addr, options, &CancelToken::new(), |_| {})
+                })
+            })
+            .collect();
+        let found = running.join().unwrap().unwrap();
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+
+        let events = events.lock().unwrap();
+        assert!(
+            events.iter().any(|event| matches!(
+                event,
+                ClusterEvent::WorkerLeft { requeued: Some(id), .. } if *id == abandoned
+            )),
+            "断开的工作端的单元应该重新排队"
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, ClusterEvent::UnitMismatch { .. }))
+        );
+        found
+    });
+
+    assert_eq!(found.unwrap().secret, b"9999");
+}
@@ -0,0 +1,229 @@
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{DecodingKey, Validation, decode, decode_header};
+use jwt_cracker::Error;
+use jwt_cracker::oracle::{self, Classifier, Oracle, RequestTemplate, TokenPlacement};
+use jwt_cracker::utils::jwt::{HmacAlgorithm, decode_json_segment, sign_hmac, split_token};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Response, Server};
+
+/// 只接受 `alg` 为小写 `none` 且没有签名的令牌，并且要求模板中的会话 Cookie 还在
+fn respond(cookie: &str) -> u16 {
+    let mut session = false;
+    let mut token = None;
+    for pair in cookie.split(';').map(str::trim) {
+        match pair.split_once('=') {
+            Some(("session", "abc")) => session = true,
+            Some(("jwt", value)) => token = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    let (true, Some(token)) = (session, token) else {
+        return 400;
+    };
+    let Ok(parts) = split_token(&token) else {
+        return 401;
+    };
+    let alg = decode_json_segment("header", parts.header)
+        .ok()
+        .and_then(|header| header.get("alg").and_then(Value::as_str).map(String::from));
+    if alg.as_deref() == Some("none") && parts.signature.is_empty() {
+        200
+    } else {
+        401
+    }
+}
+
+#[test]
+fn classifier_reports_only_the_accepted_forgery() {
+    let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+    let addr = server.server_addr().to_ip().unwrap();
+    let mock = {
+        let server = server.clone();
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let cookie = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Cookie"))
+                    .map(|header| header.value.to_string())
+                    .unwrap_or_default();
+                let status = respond(&cookie);
+                let _ = request.respond(Response::from_string("").with_status_code(status));
+            }
+        })
+    };
+
+    let token = sign_hmac(
+        &json!({ "typ": "JWT" }),
+        &json!({ "sub": "user" }),
+        HmacAlgorithm::HS256,
+        b"secret",
+    );
+    let template = RequestTemplate {
+        headers: vec![("Cookie".to_string(), "session=abc".to_string())],
+        placement: TokenPlacement::Cookie {
+            name: "jwt".to_string(),
+        },
+        ..RequestTemplate::new(&format!("http://{}/me", addr))
+    };
+    let classifier = Classifier {
+        status: vec![200],
+        ..Classifier::default()
+    };
+    let forgeries = oracle::forgeries(&token, None, None, None).unwrap();
+    let mut oracle = Oracle::new(template, classifier, 0.0, Duration::from_secs(5)).unwrap();
+    let verdicts = oracle.probe(&token, &forgeries, |_| {}).unwrap();
+    server.unblock();
+    mock.join().unwrap();
+
+    assert_eq!(verdicts.len(), forgeries.len());
+    // 会话 Cookie 被覆盖时所有请求都会得到 400
+    assert!(verdicts.iter().all(|v| v.response.status != 400));
+    let accepted: Vec<&str> = verdicts
+        .iter()
+        .filter(|v| v.accepted)
+        .map(|v| v.forgery.name.as_str())
+        .collect();
+    assert_eq!(accepted, ["alg_none"]);
+}
+
+/// 像不校验 `jku` 来源的服务端那样，从令牌指向的地址取 JWKS 验证签名
+fn verify_with_jku(token: &str) -> u16 {
+    let Ok(header) = decode_header(token) else {
+        return 401;
+    };
+    let (Some(url), Some(kid)) = (header.jku, header.kid) else {
+        return 401;
+    };
+    let Ok(response) = ureq::get(&url).call() else {
+        return 401;
+    };
+    let Ok(jwks) = serde_json::from_str::<JwkSet>(&response.into_string().unwrap()) else {
+        return 401;
+    };
+    let Some(key) = jwks
+        .find(&kid)
+        .and_then(|jwk| DecodingKey::from_jwk(jwk).ok())
+    else {
+        return 401;
+    };
+    let mut validation = Validation::new(header.alg);
+    validation.required_spec_claims = HashSet::new();
+    validation.validate_exp = false;
+    match decode::<Value>(token, &key, &validation) {
+        Ok(_) => 200,
+        Err(_) => 401,
+    }
+}
+
+#[test]
+fn jku_forgery_is_accepted_once_its_jwks_is_hosted() {
+    let jwks = Arc::new(Mutex::new(String::new()));
+    let host = Arc::new(Server::http("127.0.0.1:0").unwrap());
+    let host_addr = host.server_addr().to_ip().unwrap();
+    let hosting = {
+        let host = host.clone();
+        let jwks = jwks.clone();
+        thread::spawn(move || {
+            for request in host.incoming_requests() {
+                let body = jwks.lock().unwrap().clone();
+                let _ = request.respond(Response::from_string(body));
+            }
+        })
+    };
+    let api = Arc::new(Server::http("127.0.0.1:0").unwrap());
+    let api_addr = api.server_addr().to_ip().unwrap();
+    let serving = {
+        let api = api.clone();
+        thread::spawn(move || {
+            for request in api.incoming_requests() {
+                let token = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Authorization"))
+                    .and_then(|header| {
+                        header
+                            .value
+                            .as_str()
+                            .strip_prefix("Bearer ")
+                            .map(String::from)
+                    })
+                    .unwrap_or_default();
+                let status = verify_with_jku(&token);
+                let _ = request.respond(Response::from_string("").with_status_code(status));
+            }
+        })
+    };
+
+    let token = sign_hmac(
+        &json!({ "typ": "JWT" }),
+        &json!({ "sub": "user" }),
+        HmacAlgorithm::HS256,
+        b"secret",
+    );
+    let url = format!("http://{}/jwks.json", host_addr);
+    let forgeries = oracle::forgeries(&token, None, None, Some(&url)).unwrap();
+    let forgery = forgeries.iter().find(|f| f.name == "jku").unwrap();
+    *jwks.lock().unwrap() = forgery.hosted_document.clone().unwrap();
+
+    let classifier = Classifier {
+        status: vec![200],
+        ..Classifier::default()
+    };
+    let template = RequestTemplate::new(&format!("http://{}/me", api_addr));
+    let mut oracle = Oracle::new(template, classifier, 0.0, Duration::from_secs(5)).unwrap();
+    let verdicts = oracle.probe(&token, &forgeries, |_| {}).unwrap();
+    api.unblock();
+    host.unblock();
+    serving.join().unwrap();
+    hosting.join().unwrap();
+
+    let accepted: Vec<&str> = verdicts
+        .iter()
+        .filter(|v| v.accepted)
+        .map(|v| v.forgery.name.as_str())
+        .collect();
+    assert_eq!(accepted, ["jku"]);
+}
+
+#[test]
+fn forgeries_use_the_edited_payload() {
+    let token = sign_hmac(
+        &json!({ "typ": "JWT" }),
+        &json!({ "sub": "user" }),
+        HmacAlgorithm::HS256,
+        b"secret",
+    );
+    let payload = json!({ "sub": "admin" });
+    let forgeries = oracle::forgeries(&token, Some(&payload), Some(b"secret"), None).unwrap();
+    for forgery in forgeries.iter().filter(|f| f.name != "original") {
+        let parts = split_token(&forgery.token).unwrap();
+        assert_eq!(
+            decode_json_segment("payload", parts.payload).unwrap(),
+            payload,
+            "{}",
+            forgery.name
+        );
+    }
+    let stripped = forgeries
+        .iter()
+        .find(|f| f.name == "signature_stripped")
+        .unwrap();
+    assert!(stripped.token.ends_with('.'));
+}
+
+#[test]
+fn tiny_rate_is_rejected() {
+    let template = RequestTemplate::new("http://127.0.0.1:1/");
+    let result = Oracle::new(
+        template,
+        Classifier::default(),
+        1e-300,
+        Duration::from_secs(1),
+    );
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
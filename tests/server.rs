@@ -0,0 +1,124 @@
+use jwt_cracker::server::{ServeOptions, Server};
+use jwt_cracker::utils::jwt::{HmacAlgorithm, sign_hmac};
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn token(secret: &str) -> String {
+    sign_hmac(
+        &json!({ "typ": "JWT" }),
+        &json!({ "sub": "test" }),
+        HmacAlgorithm::HS256,
+        secret.as_bytes(),
+    )
+}
+
+fn json_body(response: ureq::Response) -> Value {
+    serde_json::from_str(&response.into_string().unwrap()).unwrap()
+}
+
+fn post_job(addr: SocketAddr, body: Value) -> u64 {
+    let response = json_body(
+        ureq::post(&format!("http://{}/jobs", addr))
+            .send_string(&body.to_string())
+            .unwrap(),
+    );
+    assert_eq!(response["status"], "queued");
+    response["id"].as_u64().unwrap()
+}
+
+fn get_job(addr: SocketAddr, id: u64) -> Value {
+    json_body(
+        ureq::get(&format!("http://{}/jobs/{}", addr, id))
+            .call()
+            .unwrap(),
+    )
+}
+
+/// 轮询任务直到状态满足 `done`
+fn wait_for(addr: SocketAddr, id: u64, done: impl Fn(&str) -> bool) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        let job = get_job(addr, id);
+        if done(job["status"].as_str().unwrap()) {
+            return job;
+        }
+        assert!(Instant::now() < deadline, "任务没有按时结束: {}", job);
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn jobs_events_and_cancellation() {
+    let server = Server::bind(&ServeOptions {
+        addr: "127.0.0.1:0".to_string(),
+        queue_size: 4,
+        workers: 1,
+    })
+    .unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let running = thread::spawn(move || server.run());
+
+    // 先订阅事件，之后提交的任务都会推送
+    let mut events = TcpStream::connect(addr).unwrap();
+    events
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .unwrap();
+    write!(events, "GET /events HTTP/1.1\r\nHost: {}\r\n\r\n", addr).unwrap();
+    let mut events = BufReader::new(events);
+    let mut line = String::new();
+    events.read_line(&mut line).unwrap();
+    assert!(line.starts_with("HTTP/1.1 200"), "{}", line);
+    // 等响应头写完，保证订阅已经生效
+    while line != "\r\n" {
+        line.clear();
+        events.read_line(&mut line).unwrap();
+    }
+
+    let id = post_job(
+        addr,
+        json!({
+            "token": token("4711"),
+            "attack": { "stages": [{ "mode": "mask", "mask": "?d?d?d?d" }] },
+            "options": { "threads": 1 },
+        }),
+    );
+    let job = wait_for(addr, id, |status| !matches!(status, "queued" | "running"));
+    assert_eq!(job["status"], "found");
+    assert_eq!(job["secret"]["utf8"], "4711");
+
+    let frame = loop {
+        line.clear();
+        assert!(events.read_line(&mut line).unwrap() > 0, "事件流提前关闭");
+        if let Some(data) = line.strip_prefix("data: ") {
+            break serde_json::from_str::<Value>(data).unwrap();
+        }
+    };
+    assert_eq!(frame["id"], id);
+
+    // 默认的暴力破解跑不完，只能取消
+    let id = post_job(
+        addr,
+        json!({ "token": token("not in the keyspace"), "options": { "threads": 1 } }),
+    );
+    wait_for(addr, id, |status| status == "running");
+    let cancelled = json_body(
+        ureq::delete(&format!("http://{}/jobs/{}", addr, id))
+            .call()
+            .unwrap(),
+    );
+    assert_eq!(cancelled["id"], id);
+    let job = wait_for(addr, id, |status| status != "running");
+    assert_eq!(job["status"], "cancelled");
+
+    match ureq::get(&format!("http://{}/jobs/9999", addr)).call() {
+        Err(ureq::Error::Status(status, _)) => assert_eq!(status, 404),
+        other => panic!("应该返回 404: {:?}", other.map(|r| r.status())),
+    }
+
+    shutdown.shutdown();
+    running.join().unwrap().unwrap();
+}